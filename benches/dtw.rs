@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use clam::Metric;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use radio_ml::metrics::{DynamicTimeWarping, Minkowski};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+const FRAME_LEN: usize = 1024;
+
+/// A noisy tone of random frequency, standing in for one channel of a
+/// frame from the reader, so that the benchmark does not need the dataset.
+fn frame(seed: u64) -> Vec<f64> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let freq = rng.gen_range(0.01..0.05);
+    (0..FRAME_LEN)
+        .map(|t| (2. * PI * freq * t as f64).sin() + rng.gen_range(-0.1..0.1))
+        .collect()
+}
+
+/// DTW over the full `(n + 1) x (m + 1)` cost matrix, as a baseline for the
+/// two-row fill in `DynamicTimeWarping`.
+fn full_matrix_dtw(x: &[f64], y: &[f64]) -> f64 {
+    let mut cost = vec![vec![f64::INFINITY; y.len() + 1]; x.len() + 1];
+    cost[0][0] = 0.;
+    for (i, &a) in x.iter().enumerate() {
+        for (j, &b) in y.iter().enumerate() {
+            cost[i + 1][j + 1] = (a - b).abs() + cost[i][j].min(cost[i][j + 1]).min(cost[i + 1][j]);
+        }
+    }
+    cost[x.len()][y.len()]
+}
+
+fn dtw(c: &mut Criterion) {
+    let (x, y) = (frame(0), frame(1));
+
+    let mut group = c.benchmark_group("dtw_1024");
+    group.throughput(Throughput::Elements((FRAME_LEN * FRAME_LEN) as u64));
+
+    group.bench_function("full_matrix", |b| {
+        b.iter(|| full_matrix_dtw(criterion::black_box(&x), criterion::black_box(&y)))
+    });
+
+    let two_row = DynamicTimeWarping::<f64, f64>::new(Arc::new(Minkowski::new(2.)));
+    group.bench_function("two_row", |b| {
+        b.iter(|| two_row.one_to_one(criterion::black_box(&x), criterion::black_box(&y)))
+    });
+
+    let itakura = DynamicTimeWarping::<f64, f64>::with_itakura(Arc::new(Minkowski::new(2.)), 2.);
+    group.bench_function("itakura_2", |b| {
+        b.iter(|| itakura.one_to_one(criterion::black_box(&x), criterion::black_box(&y)))
+    });
+
+    let sakoe_chiba =
+        DynamicTimeWarping::<f64, f64>::with_sakoe_chiba(Arc::new(Minkowski::new(2.)), 64);
+    group.bench_function("sakoe_chiba_64", |b| {
+        b.iter(|| sakoe_chiba.one_to_one(criterion::black_box(&x), criterion::black_box(&y)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, dtw);
+criterion_main!(benches);
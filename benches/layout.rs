@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ndarray::prelude::*;
+use radio_ml::reader::{ModulationMode, RadioData, RadioReader};
+
+const MODES: [ModulationMode; 3] = [
+    ModulationMode::BPSK,
+    ModulationMode::QPSK,
+    ModulationMode::PSK_8,
+];
+const FRAMES_PER_LEVEL: usize = 16;
+
+/// Writes a file in the original layout for each of `MODES`, so that the
+/// benchmark does not need the dataset.
+fn fixture_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("radio_ml_layout_bench");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for mode in MODES {
+        let x = Array3::from_shape_fn((26 * FRAMES_PER_LEVEL, 1024, 2), |(f, t, c)| {
+            ((f * 2048 + t * 2 + c) as f64).sin()
+        });
+        let file = hdf5::File::create(dir.join(mode.data_name())).unwrap();
+        file.new_dataset_builder()
+            .with_data(&x)
+            .create("X")
+            .unwrap();
+    }
+    dir
+}
+
+fn read(dir: &Path) -> RadioData {
+    RadioReader::new(dir)
+        .modes(&MODES)
+        .num_samples(FRAMES_PER_LEVEL)
+        .build()
+        .unwrap()
+}
+
+/// Sums the energy of each frame in row-major order, as downstream code
+/// that walks the joined array frame by frame would.
+fn frame_energies(joined: ArrayView3<f64>) -> Vec<f64> {
+    joined
+        .outer_iter()
+        .map(|frame| frame.iter().map(|v| v * v).sum())
+        .collect()
+}
+
+/// Compares `RadioData::join` with `join_contiguous`, and row-major
+/// iteration over what each returns.
+fn layout(c: &mut Criterion) {
+    let dir = fixture_dir();
+    let joined = read(&dir).join();
+    let contiguous = read(&dir).join_contiguous();
+    assert_eq!(joined, contiguous);
+    assert!(contiguous.is_standard_layout());
+
+    let mut group = c.benchmark_group("layout");
+    group.bench_function("join", |b| {
+        b.iter_batched(|| read(&dir), |data| data.join(), BatchSize::LargeInput)
+    });
+    group.bench_function("join_contiguous", |b| {
+        b.iter_batched(
+            || read(&dir),
+            |data| data.join_contiguous(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("iterate_join", |b| {
+        b.iter(|| frame_energies(criterion::black_box(joined.view())))
+    });
+    group.bench_function("iterate_join_contiguous", |b| {
+        b.iter(|| frame_energies(criterion::black_box(contiguous.view())))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, layout);
+criterion_main!(benches);
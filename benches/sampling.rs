@@ -0,0 +1,120 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+/// The system allocator, counting allocations and the bytes they request so
+/// that the two sampling strategies can be compared.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const LEVELS: usize = 26;
+const FRAMES_PER_LEVEL: usize = 64;
+const NUM_SAMPLES: usize = 16;
+
+/// A stand-in for a file's `X`, so that the benchmark does not need the
+/// dataset, with the sorted frame indices to sample from each level.
+fn fixture() -> (Array3<f64>, Vec<Vec<usize>>) {
+    let all_iq = Array3::from_shape_fn((LEVELS * FRAMES_PER_LEVEL, 1024, 2), |(f, t, c)| {
+        (f * 2048 + t * 2 + c) as f64
+    });
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let indices = (0..LEVELS)
+        .map(|_| {
+            let mut indices = (0..FRAMES_PER_LEVEL).choose_multiple(&mut rng, NUM_SAMPLES);
+            indices.sort();
+            indices
+        })
+        .collect();
+    (all_iq, indices)
+}
+
+/// The previous read path: copy each level out of `X`, then stack views of
+/// the sampled rows into another copy.
+fn owned_then_stacked(all_iq: ArrayView3<f64>, indices: &[Vec<usize>]) -> Vec<Array3<f64>> {
+    indices
+        .iter()
+        .enumerate()
+        .map(|(l, indices)| {
+            let block = all_iq
+                .slice(s![l * FRAMES_PER_LEVEL..(l + 1) * FRAMES_PER_LEVEL, .., ..])
+                .to_owned();
+            let rows = indices
+                .iter()
+                .map(|&i| block.index_axis(Axis(0), i))
+                .collect::<Vec<_>>();
+            ndarray::stack(Axis(0), &rows).unwrap()
+        })
+        .collect()
+}
+
+/// The current read path in `RadioFile::sample_frames`: one `select` per
+/// level straight out of `X`.
+fn selected(all_iq: ArrayView3<f64>, indices: &[Vec<usize>]) -> Vec<Array3<f64>> {
+    indices
+        .iter()
+        .enumerate()
+        .map(|(l, indices)| {
+            all_iq
+                .slice(s![l * FRAMES_PER_LEVEL..(l + 1) * FRAMES_PER_LEVEL, .., ..])
+                .select(Axis(0), indices)
+        })
+        .collect()
+}
+
+/// The allocations and bytes allocated by one call of `f`.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize, usize) {
+    let (allocations, bytes) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        BYTES.load(Ordering::Relaxed),
+    );
+    let result = f();
+    (
+        result,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn sampling(c: &mut Criterion) {
+    let (all_iq, indices) = fixture();
+
+    let (old, old_allocations, old_bytes) =
+        count_allocations(|| owned_then_stacked(all_iq.view(), &indices));
+    let (new, new_allocations, new_bytes) = count_allocations(|| selected(all_iq.view(), &indices));
+    assert_eq!(old, new, "both strategies must sample the same frames");
+    println!("owned_then_stacked: {old_allocations} allocations, {old_bytes} bytes");
+    println!("select: {new_allocations} allocations, {new_bytes} bytes");
+
+    let mut group = c.benchmark_group("sampling");
+    group.bench_function("owned_then_stacked", |b| {
+        b.iter(|| owned_then_stacked(criterion::black_box(all_iq.view()), &indices))
+    });
+    group.bench_function("select", |b| {
+        b.iter(|| selected(criterion::black_box(all_iq.view()), &indices))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sampling);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Simulated channel and hardware impairments for data augmentation.
+
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+use num_complex::Complex;
+
+use crate::iq;
+
+/// Applies a carrier frequency offset to a `(n, 2)` frame by multiplying
+/// the complex signal by `exp(j 2 pi f t)`, where `f` is in cycles per
+/// sample.
+pub fn apply_cfo(frame: ArrayView2<f64>, normalized_freq: f64) -> Array2<f64> {
+    let signal = iq::to_complex(frame)
+        .into_iter()
+        .enumerate()
+        .map(|(t, c)| c * Complex::from_polar(1., 2. * PI * normalized_freq * t as f64))
+        .collect::<Vec<_>>();
+    iq::from_complex(&signal)
+}
+
+/// Applies IQ gain and phase imbalance to a `(n, 2)` frame.
+///
+/// I is left as is and Q becomes `g (Q cos(phi) - I sin(phi))`, where `g`
+/// is `gain_db` as an amplitude ratio and `phi` is `phase_deg` in radians,
+/// i.e. the Q branch is mixed with a carrier that is `phi` off quadrature.
+/// `correct_iq_imbalance` undoes this.
+pub fn apply_iq_imbalance(frame: ArrayView2<f64>, gain_db: f64, phase_deg: f64) -> Array2<f64> {
+    let gain = 10_f64.powf(gain_db / 20.);
+    let (sin, cos) = phase_deg.to_radians().sin_cos();
+
+    let mut imbalanced = frame.to_owned();
+    imbalanced
+        .outer_iter_mut()
+        .for_each(|mut row| row[1] = gain * (row[1] * cos - row[0] * sin));
+    imbalanced
+}
+
+/// Blindly removes IQ gain and phase imbalance from a `(n, 2)` frame.
+///
+/// This orthogonalizes Q against I and rescales it to the power of I, which
+/// whitens the two components. It assumes the clean signal is zero-mean and
+/// circular, i.e. that its I and Q are uncorrelated and of equal power, as
+/// for most PSK and QAM constellations over a long enough frame. Frames with
+/// no power in I, or with Q entirely explained by I, are returned as is.
+pub fn correct_iq_imbalance(frame: ArrayView2<f64>) -> Array2<f64> {
+    let i = frame.column(0);
+    let q = frame.column(1);
+    let power_i = i.dot(&i);
+    if power_i <= 0. {
+        return frame.to_owned();
+    }
+
+    let orthogonal = &q - &(&i * (i.dot(&q) / power_i));
+    let power_orthogonal = orthogonal.dot(&orthogonal);
+    if power_orthogonal <= 0. {
+        return frame.to_owned();
+    }
+
+    let mut corrected = frame.to_owned();
+    corrected
+        .column_mut(1)
+        .assign(&(orthogonal * (power_i / power_orthogonal).sqrt()));
+    corrected
+}
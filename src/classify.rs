@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use clam::Metric;
+use ndarray::prelude::*;
+
+use crate::reader::ModulationMode;
+
+/// A k-nearest-neighbor modulation classifier.
+///
+/// Frames are flattened to interleaved IQ slices before being passed to the
+/// metric.
+#[derive(Debug)]
+pub struct KnnClassifier {
+    train: Array3<f64>,
+    labels: Array1<usize>,
+    k: usize,
+    metric: Box<dyn Metric<f64, f32>>,
+}
+
+impl KnnClassifier {
+    pub fn new(
+        train: Array3<f64>,
+        labels: Array1<usize>,
+        k: usize,
+        metric: Box<dyn Metric<f64, f32>>,
+    ) -> Result<Self, String> {
+        if train.shape()[0] != labels.len() {
+            return Err(format!(
+                "Got {} training frames but {} labels",
+                train.shape()[0],
+                labels.len()
+            ));
+        }
+        if k == 0 || k > labels.len() {
+            return Err(format!("k must be in 1..={}, got {}", labels.len(), k));
+        }
+        let num_modes = ModulationMode::variants().len();
+        if let Some(&l) = labels.iter().find(|&&l| l >= num_modes) {
+            return Err(format!(
+                "Label {} is not the index of one of the {} modulation modes",
+                l, num_modes
+            ));
+        }
+        Ok(Self {
+            train,
+            labels,
+            k,
+            metric,
+        })
+    }
+
+    /// The `k` nearest training frames as `(label, distance)` pairs, nearest
+    /// first.
+    fn neighbors(&self, frame: ArrayView2<f64>) -> Vec<(usize, f32)> {
+        let query = frame.iter().copied().collect::<Vec<_>>();
+        let mut distances = self
+            .train
+            .outer_iter()
+            .zip(self.labels.iter())
+            .map(|(candidate, &l)| {
+                let candidate = candidate.iter().copied().collect::<Vec<_>>();
+                (l, self.metric.one_to_one(&query, &candidate))
+            })
+            .collect::<Vec<_>>();
+        distances.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        distances.truncate(self.k);
+        distances
+    }
+
+    /// Majority vote among the `k` nearest training frames. Ties go to the
+    /// class with the nearest neighbor.
+    pub fn predict(&self, frame: ArrayView2<f64>) -> ModulationMode {
+        let neighbors = self.neighbors(frame);
+
+        let mut votes = vec![0_usize; ModulationMode::variants().len()];
+        neighbors.iter().for_each(|&(l, _)| votes[l] += 1);
+
+        let most = votes.iter().copied().max().unwrap();
+        let label = neighbors
+            .iter()
+            .map(|&(l, _)| l)
+            .find(|&l| votes[l] == most)
+            .unwrap();
+        ModulationMode::from_index(label).expect("training labels must be modulation indices")
+    }
+
+    /// Like `predict`, but each of the `k` nearest neighbors votes with
+    /// weight `1 / (distance + WEIGHT_EPSILON)`. If any neighbor matches the
+    /// frame exactly, only the exact matches vote, each with equal weight.
+    ///
+    /// Returns the predicted mode along with its share of the total weight,
+    /// in `(0, 1]`, as a confidence. Neighbors at an infinite or NaN distance
+    /// carry no weight, and if none carry any, the nearest neighbor's mode is
+    /// returned with a confidence of 0.
+    pub fn predict_weighted(&self, frame: ArrayView2<f64>) -> (ModulationMode, f64) {
+        let neighbors = self.neighbors(frame);
+        let has_exact = neighbors.iter().any(|&(_, d)| d == 0.);
+
+        let mut weights = vec![0_f64; ModulationMode::variants().len()];
+        for &(l, d) in &neighbors {
+            let weight = match (has_exact, d == 0.) {
+                (true, true) => 1.,
+                (true, false) => 0.,
+                (false, _) => 1. / (f64::from(d) + WEIGHT_EPSILON),
+            };
+            if weight.is_finite() {
+                weights[l] += weight;
+            }
+        }
+
+        // Ties go to the class with the nearest neighbor.
+        let label = neighbors.iter().fold(neighbors[0].0, |best, &(l, _)| {
+            if weights[l] > weights[best] {
+                l
+            } else {
+                best
+            }
+        });
+        let total = weights.iter().sum::<f64>();
+        let confidence = if total > 0. {
+            weights[label] / total
+        } else {
+            0.
+        };
+        let mode =
+            ModulationMode::from_index(label).expect("training labels must be modulation indices");
+        (mode, confidence)
+    }
+}
+
+/// Added to neighbor distances in `KnnClassifier::predict_weighted` to keep
+/// the weights finite.
+const WEIGHT_EPSILON: f64 = 1e-9;
+
+/// Counts of `(true, predicted)` label pairs as an `(n_classes, n_classes)`
+/// matrix, with true labels along the rows.
+pub fn confusion_matrix(
+    true_labels: &[usize],
+    predicted: &[usize],
+    n_classes: usize,
+) -> Result<Array2<usize>, String> {
+    check_same_len(true_labels, predicted)?;
+    let mut matrix = Array2::zeros((n_classes, n_classes));
+    for (&t, &p) in true_labels.iter().zip(predicted.iter()) {
+        if t >= n_classes || p >= n_classes {
+            return Err(format!(
+                "Labels ({}, {}) out of range for {} classes",
+                t, p, n_classes
+            ));
+        }
+        matrix[[t, p]] += 1;
+    }
+    Ok(matrix)
+}
+
+/// Fraction of predictions that match the true labels.
+pub fn accuracy(true_labels: &[usize], predicted: &[usize]) -> Result<f64, String> {
+    check_same_len(true_labels, predicted)?;
+    if true_labels.is_empty() {
+        return Err("Cannot compute accuracy of zero predictions".to_string());
+    }
+    let correct = true_labels
+        .iter()
+        .zip(predicted.iter())
+        .filter(|(t, p)| t == p)
+        .count();
+    Ok(correct as f64 / true_labels.len() as f64)
+}
+
+/// Accuracy of the predictions at each SNR, keyed by the SNR of the frames
+/// they were made on, e.g. from `RadioFile::snr_per_frame`.
+pub fn accuracy_by_snr(
+    true_labels: &[usize],
+    predicted: &[usize],
+    snrs: &[i32],
+) -> Result<BTreeMap<i32, f64>, String> {
+    check_same_len(true_labels, predicted)?;
+    if snrs.len() != true_labels.len() {
+        return Err(format!(
+            "Got {} true labels but {} SNRs",
+            true_labels.len(),
+            snrs.len()
+        ));
+    }
+
+    let mut counts = BTreeMap::<i32, (usize, usize)>::new();
+    for ((t, p), &snr) in true_labels.iter().zip(predicted.iter()).zip(snrs.iter()) {
+        let (correct, total) = counts.entry(snr).or_default();
+        *correct += usize::from(t == p);
+        *total += 1;
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(snr, (correct, total))| (snr, correct as f64 / total as f64))
+        .collect())
+}
+
+fn check_same_len(true_labels: &[usize], predicted: &[usize]) -> Result<(), String> {
+    if true_labels.len() == predicted.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Got {} true labels but {} predictions",
+            true_labels.len(),
+            predicted.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Minkowski;
+
+    fn classifier(labels: Vec<usize>) -> Result<KnnClassifier, String> {
+        let train = Array3::from_shape_fn((labels.len(), 4, 2), |(f, _, _)| f as f64);
+        KnnClassifier::new(train, Array1::from(labels), 1, Box::new(Minkowski::new(2.)))
+    }
+
+    #[test]
+    fn rejects_labels_that_are_not_modes() {
+        assert!(classifier(vec![0, 25]).is_ok());
+        assert!(classifier(vec![0, 26]).is_err());
+    }
+
+    #[test]
+    fn nan_distances_do_not_panic() {
+        let mut train = Array3::ones((2, 4, 2));
+        train.slice_mut(s![0, .., ..]).fill(f64::NAN);
+        let labels = array![ModulationMode::QPSK.index(), ModulationMode::BPSK.index()];
+        let knn = KnnClassifier::new(train, labels, 1, Box::new(Minkowski::new(2.))).unwrap();
+        knn.predict(Array2::ones((4, 2)).view());
+    }
+
+    /// A classifier over `train` with one label per frame and `k = 3`.
+    fn weighted_classifier(train: Array3<f64>, labels: &[ModulationMode]) -> KnnClassifier {
+        let labels = labels.iter().map(|m| m.index()).collect::<Array1<_>>();
+        KnnClassifier::new(train, labels, 3, Box::new(Minkowski::new(2.))).unwrap()
+    }
+
+    #[test]
+    fn exact_matches_dominate_the_weighted_vote() {
+        let mut train = Array3::ones((3, 4, 2));
+        train.slice_mut(s![1.., .., ..]).fill(1.001);
+        let labels = [
+            ModulationMode::BPSK,
+            ModulationMode::QPSK,
+            ModulationMode::QPSK,
+        ];
+        let knn = weighted_classifier(train, &labels);
+        assert_eq!(
+            knn.predict_weighted(Array2::ones((4, 2)).view()),
+            (ModulationMode::BPSK, 1.)
+        );
+    }
+
+    #[test]
+    fn weightless_neighbors_give_zero_confidence() {
+        let labels = [
+            ModulationMode::BPSK,
+            ModulationMode::QPSK,
+            ModulationMode::QPSK,
+        ];
+        let query = Array2::ones((4, 2));
+
+        // Every distance is NaN.
+        let knn = weighted_classifier(Array3::from_elem((3, 4, 2), f64::NAN), &labels);
+        assert_eq!(knn.predict_weighted(query.view()).1, 0.);
+
+        // Every distance overflows to infinity.
+        let knn = weighted_classifier(Array3::from_elem((3, 4, 2), 1e300), &labels);
+        let (mode, confidence) = knn.predict_weighted(query.view());
+        assert_eq!(confidence, 0.);
+        assert!(labels.contains(&mode));
+    }
+}
@@ -0,0 +1,200 @@
+//! Reading the RadioML 2016.10 dataset, which has 11 modulation classes and
+//! frames of 128 time steps.
+//!
+//! The dataset is distributed as a pickled dict from `(mode, snr)` to an
+//! array of shape `(1000, 2, 128)`. This reader expects it converted to a
+//! single HDF5 file with one dataset per key, named `{mode}_{snr}`, e.g.
+//! with:
+//!
+//! ```python
+//! with h5py.File("RML2016.10a.h5", "w") as f:
+//!     for (mode, snr), x in data.items():
+//!         f[f"{mode}_{snr}"] = x
+//! ```
+
+use std::path::Path;
+
+use ndarray::prelude::*;
+
+use crate::reader::{self, ModulationSet};
+
+/// The number of time steps in each frame.
+pub const FRAME_LEN: usize = 128;
+
+/// The modulation classes of the 2016.10 dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[allow(non_camel_case_types)]
+pub enum Mode2016 {
+    PSK_8,
+    AM_DSB,
+    AM_SSB,
+    BPSK,
+    CPFSK,
+    GFSK,
+    PAM_4,
+    QAM_16,
+    QAM_64,
+    QPSK,
+    WBFM,
+}
+
+impl Mode2016 {
+    /// Position of this mode in `variants`, used as its integer label.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The inverse of `index`.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::variants().get(index).copied()
+    }
+
+    /// The name of this mode in the dataset's keys.
+    pub fn key_name(&self) -> &str {
+        match self {
+            Self::PSK_8 => "8PSK",
+            Self::AM_DSB => "AM-DSB",
+            Self::AM_SSB => "AM-SSB",
+            Self::BPSK => "BPSK",
+            Self::CPFSK => "CPFSK",
+            Self::GFSK => "GFSK",
+            Self::PAM_4 => "PAM4",
+            Self::QAM_16 => "QAM16",
+            Self::QAM_64 => "QAM64",
+            Self::QPSK => "QPSK",
+            Self::WBFM => "WBFM",
+        }
+    }
+
+    pub fn variants() -> [Mode2016; 11] {
+        [
+            Self::PSK_8,
+            Self::AM_DSB,
+            Self::AM_SSB,
+            Self::BPSK,
+            Self::CPFSK,
+            Self::GFSK,
+            Self::PAM_4,
+            Self::QAM_16,
+            Self::QAM_64,
+            Self::QPSK,
+            Self::WBFM,
+        ]
+    }
+
+    /// The SNR levels, in dB, of every mode.
+    pub fn snrs() -> Vec<i32> {
+        (-20..=18).step_by(2).collect()
+    }
+}
+
+impl ModulationSet for Mode2016 {
+    fn label(&self) -> usize {
+        self.index()
+    }
+}
+
+/// Frames sampled from each SNR level of each mode of the 2016.10 dataset,
+/// as `RadioData` does for the 2018 one.
+#[derive(Debug)]
+pub struct Dataset2016 {
+    modes: Vec<Mode2016>,
+    samples: Vec<Array3<f64>>,
+    snrs: Vec<Array1<i32>>,
+}
+
+impl Dataset2016 {
+    /// Samples `num_samples` frames from each SNR level of every mode in the
+    /// HDF5 file at `path`. Frames are transposed to `(128, 2)` so that they
+    /// have the same I/Q layout as the 2018 dataset.
+    pub fn read(path: impl AsRef<Path>, num_samples: usize) -> Result<Self, String> {
+        Self::read_seeded(path, num_samples, reader::DEFAULT_SEED)
+    }
+
+    /// Like `read`, but sampling with the given `seed`.
+    pub fn read_seeded(
+        path: impl AsRef<Path>,
+        num_samples: usize,
+        seed: u64,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let handle = hdf5::File::open(path)
+            .map_err(|reason| format!("Could not open {:?} because {}", path, reason))?;
+
+        let snr_levels = Mode2016::snrs();
+        let modes = Mode2016::variants().to_vec();
+        let mut samples = Vec::with_capacity(modes.len());
+        let mut snrs = Vec::with_capacity(modes.len());
+        for &mode in &modes {
+            let mut blocks = Vec::with_capacity(snr_levels.len());
+            let mut block_snrs = Vec::new();
+            for &snr in &snr_levels {
+                let name = format!("{}_{}", mode.key_name(), snr);
+                let all_iq: Array3<f64> = handle
+                    .dataset(&name)
+                    .map_err(|reason| format!("Could not read `{}` because {}", name, reason))?
+                    .read()
+                    .map_err(|reason| {
+                        format!(
+                            "Could not convert `{}` from HDF5 to Array3<f64> because {}",
+                            name, reason
+                        )
+                    })?;
+                if all_iq.shape()[1..] != [2, FRAME_LEN] {
+                    return Err(format!(
+                        "`{}` had data of shape {:?} instead of [_, 2, {}]",
+                        name,
+                        all_iq.shape(),
+                        FRAME_LEN
+                    ));
+                }
+
+                let num_frames = all_iq.shape()[0];
+                reader::check_sample_counts(&[num_samples], num_frames)?;
+                let indices = reader::sample_indices(num_frames, num_samples, seed);
+                let iq = all_iq
+                    .select(Axis(0), &indices)
+                    .permuted_axes([0, 2, 1])
+                    .as_standard_layout()
+                    .into_owned();
+                blocks.push(iq);
+                block_snrs.extend(std::iter::repeat(snr).take(indices.len()));
+            }
+
+            let blocks = blocks.iter().map(|b| b.view()).collect::<Vec<_>>();
+            samples.push(ndarray::concatenate(Axis(0), &blocks).unwrap());
+            snrs.push(Array1::from(block_snrs));
+        }
+
+        Ok(Self {
+            modes,
+            samples,
+            snrs,
+        })
+    }
+
+    /// The label of each frame from `join`.
+    pub fn labels(&self) -> Array1<usize> {
+        reader::labels_of(&self.modes, &self.samples)
+    }
+
+    /// The SNR, in dB, of each frame from `join`.
+    pub fn snrs(&self) -> Array1<i32> {
+        let snrs = self.snrs.iter().map(|s| s.view()).collect::<Vec<_>>();
+        ndarray::concatenate(Axis(0), &snrs).unwrap()
+    }
+
+    /// The samples read for `mode`.
+    pub fn samples_for(&self, mode: Mode2016) -> Option<ArrayView3<f64>> {
+        self.modes
+            .iter()
+            .position(|&m| m == mode)
+            .map(|i| self.samples[i].view())
+    }
+
+    /// All samples as one `(n_frames, 128, 2)` array, grouped by mode.
+    pub fn join(self) -> Array3<f64> {
+        let samples = self.samples.iter().map(|s| s.view()).collect::<Vec<_>>();
+        ndarray::concatenate(Axis(0), &samples).unwrap()
+    }
+}
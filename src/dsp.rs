@@ -0,0 +1,93 @@
+//! General signal processing helpers on `(n, 2)` IQ frames.
+
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+
+/// Resamples a `(n, 2)` frame to `(target_len, 2)` by linear interpolation
+/// of I and Q independently.
+///
+/// The first and last samples of the frame are kept at the ends of the
+/// output. Linear interpolation is not band-limited, so downsampling by a
+/// large factor will alias; low-pass filter first if that matters.
+pub fn resample(frame: ArrayView2<f64>, target_len: usize) -> Array2<f64> {
+    let n = frame.nrows();
+    let mut resampled = Array2::zeros((target_len, frame.ncols()));
+    if n == 0 || target_len == 0 {
+        return resampled;
+    }
+    if n == 1 || target_len == 1 {
+        resampled
+            .outer_iter_mut()
+            .for_each(|mut row| row.assign(&frame.row(0)));
+        return resampled;
+    }
+
+    let scale = (n - 1) as f64 / (target_len - 1) as f64;
+    for (k, mut row) in resampled.outer_iter_mut().enumerate() {
+        let position = k as f64 * scale;
+        let lo = (position.floor() as usize).min(n - 2);
+        let fraction = position - lo as f64;
+        let interpolated = &frame.row(lo) * (1. - fraction) + &frame.row(lo + 1) * fraction;
+        row.assign(&interpolated);
+    }
+    resampled
+}
+
+/// Unwraps a sequence of phases in radians, e.g. from `iq::phase`, by adding
+/// multiples of `2 pi` wherever consecutive phases jump by more than `pi`,
+/// so that each jump lands in `[-pi, pi)`.
+///
+/// A ramp that wraps several times becomes a straight line. A phase that
+/// oscillates around `+-pi` stays near `pi` instead of flipping sign, which
+/// keeps noise at the branch cut from showing up as `2 pi` steps.
+pub fn unwrap_phase(phase: ArrayView1<f64>) -> Array1<f64> {
+    let mut unwrapped = Vec::with_capacity(phase.len());
+    let mut offset = 0.;
+    let mut previous = None;
+    for &p in phase.iter() {
+        if let Some(previous) = previous {
+            let jump = p - previous;
+            if jump.abs() > PI {
+                let wrapped = (jump + PI).rem_euclid(2. * PI) - PI;
+                offset += wrapped - jump;
+            }
+        }
+        unwrapped.push(p + offset);
+        previous = Some(p);
+    }
+    Array1::from(unwrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `phase` wrapped into `(-pi, pi]`.
+    fn wrap(phase: f64) -> f64 {
+        phase.sin().atan2(phase.cos())
+    }
+
+    #[test]
+    fn unwraps_a_ramp_that_wraps_many_times() {
+        let ramp = (0..100).map(|t| 0.4 * t as f64).collect::<Array1<_>>();
+        let wrapped = ramp.mapv(wrap);
+        assert!(wrapped.iter().all(|p| p.abs() <= PI));
+
+        let unwrapped = unwrap_phase(wrapped.view());
+        let error = (&unwrapped - &ramp).fold(0., |m: f64, &e| m.max(e.abs()));
+        assert!(error < 1e-9, "{error}");
+    }
+
+    #[test]
+    fn keeps_noise_at_the_branch_cut_continuous() {
+        let noisy = (0..50)
+            .map(|t| wrap(PI + if t % 2 == 0 { -0.05 } else { 0.05 }))
+            .collect::<Array1<_>>();
+        let unwrapped = unwrap_phase(noisy.view());
+        assert!(
+            unwrapped.iter().all(|p| (p - PI).abs() < 0.1),
+            "{unwrapped}"
+        );
+    }
+}
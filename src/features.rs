@@ -0,0 +1,255 @@
+//! Hand-crafted features for modulation classification.
+
+use ndarray::prelude::*;
+use num_complex::Complex;
+
+use crate::{dsp, iq};
+
+/// Instantaneous frequency of a `(n, 2)` frame, in radians per sample.
+///
+/// This is the first difference of the unwrapped phase, so the result has
+/// `n - 1` elements.
+pub fn instantaneous_frequency(frame: ArrayView2<f64>) -> Array1<f64> {
+    let unwrapped = dsp::unwrap_phase(iq::phase(frame).view());
+    unwrapped
+        .iter()
+        .zip(unwrapped.iter().skip(1))
+        .map(|(a, b)| b - a)
+        .collect()
+}
+
+/// Second and fourth order moments `M_pq = E[y^(p-q) conj(y)^q]` of the
+/// complex signal `y`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moments {
+    pub m20: Complex<f64>,
+    pub m21: f64,
+    pub m40: Complex<f64>,
+    pub m41: Complex<f64>,
+    pub m42: f64,
+}
+
+/// Second and fourth order cumulants of the complex signal, as used for
+/// automatic modulation classification (Swami & Sadler, 2000).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cumulants {
+    pub c20: Complex<f64>,
+    pub c21: f64,
+    pub c40: Complex<f64>,
+    pub c41: Complex<f64>,
+    pub c42: f64,
+}
+
+/// Moments of the complex signal in a `(n, 2)` frame.
+pub fn moments(frame: ArrayView2<f64>) -> Moments {
+    let signal = iq::to_complex(frame);
+    let n = signal.len() as f64;
+    let mean = |f: &dyn Fn(Complex<f64>) -> Complex<f64>| {
+        signal.iter().map(|&y| f(y)).sum::<Complex<f64>>() / n
+    };
+
+    Moments {
+        m20: mean(&|y| y * y),
+        m21: mean(&|y| y * y.conj()).re,
+        m40: mean(&|y| y.powi(4)),
+        m41: mean(&|y| y.powi(3) * y.conj()),
+        m42: mean(&|y| (y * y.conj()).powi(2)).re,
+    }
+}
+
+/// Cumulants derived from `moments`, assuming a zero-mean signal.
+pub fn cumulants(moments: &Moments) -> Cumulants {
+    let Moments {
+        m20,
+        m21,
+        m40,
+        m41,
+        m42,
+    } = *moments;
+
+    Cumulants {
+        c20: m20,
+        c21: m21,
+        c40: m40 - 3. * m20 * m20,
+        c41: m41 - 3. * m20 * m21,
+        c42: m42 - m20.norm_sqr() - 2. * m21 * m21,
+    }
+}
+
+/// Z-scores each column of an `(n_frames, n_features)` matrix in place.
+///
+/// Returns the column means and (population) standard deviations so the
+/// same transform can be applied to other data. Columns with zero variance
+/// are only centered, leaving them at zero.
+pub fn standardize(features: &mut Array2<f64>) -> (Array1<f64>, Array1<f64>) {
+    let means = features
+        .mean_axis(Axis(0))
+        .unwrap_or_else(|| Array1::zeros(features.ncols()));
+    let stds = features.std_axis(Axis(0), 0.);
+
+    features
+        .axis_iter_mut(Axis(1))
+        .zip(means.iter().zip(stds.iter()))
+        .for_each(|(mut column, (&mean, &std))| {
+            if std > 0. {
+                column.mapv_inplace(|v| (v - mean) / std);
+            } else {
+                column.fill(0.);
+            }
+        });
+
+    (means, stds)
+}
+
+/// Estimates the SNR of a `(n, 2)` frame in dB with the M2M4 estimator
+/// (Pauluzzi & Beaulieu, 2000), computed from `moments`.
+///
+/// The estimator assumes a constant-modulus constellation (e.g. PSK or FM)
+/// in zero-mean circular complex Gaussian noise, where
+/// `S = sqrt(2 M21^2 - M42)` and `N = M21 - S`. Frames from other
+/// constellations, such as QAM or PAM, bias it downward, and it is noisy for
+/// short frames, so treat it as a rough check against the SNR label rather
+/// than ground truth.
+///
+/// Returns `f64::NEG_INFINITY` when no signal power is detected and
+/// `f64::INFINITY` when no noise power is detected.
+pub fn estimate_snr_db(frame: ArrayView2<f64>) -> f64 {
+    let Moments { m21, m42, .. } = moments(frame);
+
+    let signal = (2. * m21 * m21 - m42).max(0.).sqrt();
+    let noise = m21 - signal;
+    if signal <= 0. {
+        f64::NEG_INFINITY
+    } else if noise <= 0. {
+        f64::INFINITY
+    } else {
+        10. * (signal / noise).log10()
+    }
+}
+
+/// Counts the IQ points of a `(n, 2)` frame in a `bins x bins` grid over
+/// `[-range, range]` in both I and Q, i.e. an image of its constellation.
+///
+/// Entry `[a, b]` counts the points in the `a`-th I bin and the `b`-th Q
+/// bin, from the most negative. Points outside the range are counted in the
+/// nearest edge bin.
+pub fn constellation_histogram(frame: ArrayView2<f64>, bins: usize, range: f64) -> Array2<usize> {
+    assert_eq!(frame.ncols(), 2, "frames must have an I and a Q column");
+    assert!(bins > 0, "bins must be positive");
+    assert!(range > 0., "range must be positive, got {range}");
+
+    let bin = |v: f64| {
+        let position = (v + range) / (2. * range) * bins as f64;
+        (position.max(0.) as usize).min(bins - 1)
+    };
+    let mut histogram = Array2::zeros((bins, bins));
+    for iq in frame.outer_iter() {
+        histogram[[bin(iq[0]), bin(iq[1])]] += 1;
+    }
+    histogram
+}
+
+/// The quantiles `qs` of the magnitude of a `(n, 2)` frame over time, in the
+/// order given, e.g. `&[0.1, 0.5, 0.9]` for the decile range and median.
+///
+/// Quantiles interpolate linearly between the sorted magnitudes, so `0` and
+/// `1` give the smallest and largest. Each `q` must be in `[0, 1]`. An empty
+/// frame gives `NaN` for every quantile.
+pub fn amplitude_quantiles(frame: ArrayView2<f64>, qs: &[f64]) -> Result<Vec<f64>, String> {
+    if let Some(q) = qs.iter().find(|q| !(0. ..=1.).contains(*q)) {
+        return Err(format!("quantiles must be in [0, 1], got {}", q));
+    }
+
+    let mut magnitudes = iq::magnitude(frame).to_vec();
+    magnitudes.sort_by(f64::total_cmp);
+    let quantiles = qs
+        .iter()
+        .map(|&q| {
+            if magnitudes.is_empty() {
+                return f64::NAN;
+            }
+            let position = q * (magnitudes.len() - 1) as f64;
+            let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+            let fraction = position - lo as f64;
+            magnitudes[lo] + fraction * (magnitudes[hi] - magnitudes[lo])
+        })
+        .collect::<Vec<_>>();
+    Ok(quantiles)
+}
+
+/// The skewness `E[(x - mean)^3] / std^3` of `values`, e.g. of a frame's
+/// `iq::magnitude`, using the population standard deviation.
+///
+/// Returns 0 for constant or empty `values`.
+pub fn skewness(values: ArrayView1<f64>) -> f64 {
+    standardized_moment(values, 3)
+}
+
+/// The excess kurtosis `E[(x - mean)^4] / std^4 - 3` of `values`, which is 0
+/// for a Gaussian, using the population standard deviation.
+///
+/// Returns 0 for constant or empty `values`.
+pub fn kurtosis(values: ArrayView1<f64>) -> f64 {
+    match standardized_moment(values, 4) {
+        m if m == 0. => 0.,
+        m => m - 3.,
+    }
+}
+
+/// `E[((x - mean) / std)^k]`, or 0 if `values` has no variance.
+fn standardized_moment(values: ArrayView1<f64>, k: i32) -> f64 {
+    let mean = match values.mean() {
+        Some(mean) => mean,
+        None => return 0.,
+    };
+    let std = values.std(0.);
+    if std <= 0. {
+        return 0.;
+    }
+    values.mapv(|v| ((v - mean) / std).powi(k)).mean().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame whose magnitudes are `0, 1, ..., 100`, a discrete uniform
+    /// distribution, with the phase varying so that both channels are used.
+    fn uniform_magnitudes() -> Array2<f64> {
+        let mut frame = Array2::zeros((101, 2));
+        for (t, mut iq) in frame.outer_iter_mut().enumerate() {
+            let (sin, cos) = (t as f64 * 0.7).sin_cos();
+            iq[0] = t as f64 * cos;
+            iq[1] = t as f64 * sin;
+        }
+        frame
+    }
+
+    #[test]
+    fn quantiles_of_a_uniform_distribution() {
+        let frame = uniform_magnitudes();
+        let quantiles = amplitude_quantiles(frame.view(), &[0., 0.1, 0.25, 0.5, 0.9, 1.]).unwrap();
+        for (q, expected) in quantiles.iter().zip([0., 10., 25., 50., 90., 100.]) {
+            assert!((q - expected).abs() < 1e-9, "{q} != {expected}");
+        }
+        // Halfway between the magnitudes 50 and 51.
+        let halfway = amplitude_quantiles(frame.view(), &[0.505]).unwrap();
+        assert!((halfway[0] - 50.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quantiles_outside_the_unit_interval_are_errors() {
+        let frame = uniform_magnitudes();
+        assert!(amplitude_quantiles(frame.view(), &[0.5, 1.5]).is_err());
+        assert!(amplitude_quantiles(frame.view(), &[-0.1]).is_err());
+    }
+
+    #[test]
+    fn moments_of_a_uniform_distribution() {
+        let magnitudes = iq::magnitude(uniform_magnitudes().view());
+        assert!(skewness(magnitudes.view()).abs() < 1e-9);
+        // The excess kurtosis of a continuous uniform distribution is -1.2.
+        assert!((kurtosis(magnitudes.view()) + 1.2).abs() < 1e-2);
+        assert_eq!(kurtosis(Array1::from_elem(8, 3.).view()), 0.);
+    }
+}
@@ -0,0 +1,177 @@
+//! FIR filtering and smoothing of frames.
+
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+
+use crate::window::WindowKind;
+
+/// Convolves the complex signal of a `(n, 2)` frame with real `taps`.
+///
+/// The output has the same length as the input: the full convolution is
+/// centered on the frame, with the signal zero-padded past both ends. An
+/// odd number of taps therefore introduces no delay.
+pub fn fir_filter(frame: ArrayView2<f64>, taps: &[f64]) -> Array2<f64> {
+    let n = frame.nrows();
+    let center = taps.len().saturating_sub(1) / 2;
+    let mut filtered = Array2::zeros(frame.raw_dim());
+    for t in 0..n {
+        for (k, &tap) in taps.iter().enumerate() {
+            // Input index `t + center - k`, skipping the zero padding.
+            if let Some(s) = (t + center).checked_sub(k).filter(|&s| s < n) {
+                filtered[[t, 0]] += tap * frame[[s, 0]];
+                filtered[[t, 1]] += tap * frame[[s, 1]];
+            }
+        }
+    }
+    filtered
+}
+
+/// Windowed-sinc low-pass taps with unit gain at DC.
+///
+/// `cutoff` is in cycles per sample and must be in `(0, 0.5]`. The sinc is
+/// tapered with a Hamming window of length `n`.
+pub fn lowpass_taps(cutoff: f64, n: usize) -> Vec<f64> {
+    assert!(
+        cutoff > 0. && cutoff <= 0.5,
+        "cutoff must be in (0, 0.5], got {cutoff}"
+    );
+    let window = WindowKind::Hamming.coefficients(n);
+    let middle = (n as f64 - 1.) / 2.;
+    let taps = (0..n)
+        .map(|k| {
+            let t = k as f64 - middle;
+            let sinc = if t == 0. {
+                2. * cutoff
+            } else {
+                (2. * PI * cutoff * t).sin() / (PI * t)
+            };
+            sinc * window[k]
+        })
+        .collect::<Vec<_>>();
+    let gain = taps.iter().sum::<f64>();
+    taps.into_iter().map(|t| t / gain).collect()
+}
+
+/// Windowed-sinc band-pass taps for the band `[low, high]`, in cycles per
+/// sample, as the difference of two low-pass filters of length `n`.
+pub fn bandpass_taps(low: f64, high: f64, n: usize) -> Vec<f64> {
+    assert!(low < high, "low ({low}) must be below high ({high})");
+    lowpass_taps(high, n)
+        .into_iter()
+        .zip(lowpass_taps(low, n))
+        .map(|(h, l)| h - l)
+        .collect()
+}
+
+/// Root-raised-cosine taps with roll-off `beta`, spanning `span` symbols
+/// at `sps` samples per symbol, normalized to unit energy.
+///
+/// There are `span * sps + 1` taps, so the filter is symmetric and
+/// introduces no delay with `fir_filter`. `beta` must be in `[0, 1]`.
+pub fn rrc_taps(beta: f64, span: usize, sps: usize) -> Vec<f64> {
+    assert!(
+        (0. ..=1.).contains(&beta),
+        "beta must be in [0, 1], got {beta}"
+    );
+    assert!(sps > 0, "there must be at least one sample per symbol");
+
+    let middle = (span * sps) as f64 / 2.;
+    let taps = (0..=span * sps)
+        .map(|k| {
+            // Time in symbol periods from the center of the filter.
+            let t = (k as f64 - middle) / sps as f64;
+            if t == 0. {
+                1. - beta + 4. * beta / PI
+            } else if beta > 0. && (4. * beta * t).abs() == 1. {
+                let angle = PI / (4. * beta);
+                beta / 2_f64.sqrt() * ((1. + 2. / PI) * angle.sin() + (1. - 2. / PI) * angle.cos())
+            } else {
+                ((PI * t * (1. - beta)).sin() + 4. * beta * t * (PI * t * (1. + beta)).cos())
+                    / (PI * t * (1. - (4. * beta * t).powi(2)))
+            }
+        })
+        .collect::<Vec<_>>();
+    let energy = taps.iter().map(|t| t * t).sum::<f64>().sqrt();
+    taps.into_iter().map(|t| t / energy).collect()
+}
+
+/// Matched-filters the complex signal of a `(n, 2)` frame with a
+/// root-raised-cosine of roll-off `beta`, spanning `span` symbols at `sps`
+/// samples per symbol. See `rrc_taps`.
+pub fn rrc_filter(frame: ArrayView2<f64>, beta: f64, span: usize, sps: usize) -> Array2<f64> {
+    fir_filter(frame, &rrc_taps(beta, span, sps))
+}
+
+/// Smooths each channel of a `(n, 2)` frame with the mean of a centered
+/// window of `window` time steps.
+///
+/// The window covers `(window - 1) / 2` steps before each sample and
+/// `window / 2` after, and shrinks at the ends of the frame to the samples
+/// that exist rather than padding, so edge samples are averaged over fewer
+/// neighbors.
+pub fn moving_average(frame: ArrayView2<f64>, window: usize) -> Array2<f64> {
+    smooth(frame, window, |values| {
+        values.iter().sum::<f64>() / values.len() as f64
+    })
+}
+
+/// Like `moving_average`, but taking the median of each window, which
+/// removes impulsive noise while keeping edges. Windows with an even number
+/// of samples take the mean of the middle two.
+pub fn median_filter(frame: ArrayView2<f64>, window: usize) -> Array2<f64> {
+    smooth(frame, window, |values| {
+        values.sort_by(f64::total_cmp);
+        let middle = values.len() / 2;
+        if values.len() % 2 == 0 {
+            (values[middle - 1] + values[middle]) / 2.
+        } else {
+            values[middle]
+        }
+    })
+}
+
+/// Replaces each sample of each channel with `reduce` of the shrinking
+/// centered window around it.
+fn smooth<F>(frame: ArrayView2<f64>, window: usize, reduce: F) -> Array2<f64>
+where
+    F: Fn(&mut Vec<f64>) -> f64,
+{
+    assert!(window > 0, "window must be positive");
+    let n = frame.nrows();
+    let (before, after) = ((window - 1) / 2, window / 2);
+    let mut smoothed = Array2::zeros(frame.raw_dim());
+    for (channel, mut out) in frame.columns().into_iter().zip(smoothed.columns_mut()) {
+        for (t, value) in out.iter_mut().enumerate() {
+            let range = t.saturating_sub(before)..(t + after + 1).min(n);
+            let mut values = channel.slice(s![range]).to_vec();
+            *value = reduce(&mut values);
+        }
+    }
+    smoothed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moving_average_shrinks_the_window_at_the_edges() {
+        let frame = array![[0., 1.], [3., 1.], [6., 1.], [9., 1.], [12., 1.]];
+        let smoothed = moving_average(frame.view(), 3);
+        assert_eq!(smoothed.column(0), array![1.5, 3., 6., 9., 10.5]);
+        assert_eq!(smoothed.column(1), array![1., 1., 1., 1., 1.]);
+        assert_eq!(moving_average(frame.view(), 1), frame);
+    }
+
+    #[test]
+    fn median_filter_removes_impulses() {
+        let frame = array![[0., 0.], [100., 0.], [2., 0.], [3., -50.], [4., 0.]];
+        let smoothed = median_filter(frame.view(), 3);
+        assert_eq!(smoothed.column(0), array![50., 2., 3., 3., 3.5]);
+        assert_eq!(smoothed.column(1), array![0., 0., 0., 0., -25.]);
+        // An even window reaches one step further ahead than behind.
+        let smoothed = median_filter(frame.view(), 4);
+        assert_eq!(smoothed.column(0), array![2., 2.5, 3.5, 3., 3.5]);
+    }
+}
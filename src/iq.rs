@@ -0,0 +1,41 @@
+//! Conversions between the `(1024, 2)` real IQ layout used by the reader and
+//! complex samples.
+
+use ndarray::prelude::*;
+use num_complex::Complex;
+
+/// The complex signal `i + jq` for each time step of a `(n, 2)` frame.
+pub fn to_complex(frame: ArrayView2<f64>) -> Vec<Complex<f64>> {
+    assert_eq!(frame.ncols(), 2, "frames must have an I and a Q column");
+    frame
+        .outer_iter()
+        .map(|iq| Complex::new(iq[0], iq[1]))
+        .collect()
+}
+
+/// The inverse of `to_complex`.
+pub fn from_complex(signal: &[Complex<f64>]) -> Array2<f64> {
+    let mut frame = Array2::zeros((signal.len(), 2));
+    frame
+        .outer_iter_mut()
+        .zip(signal.iter())
+        .for_each(|(mut iq, c)| {
+            iq[0] = c.re;
+            iq[1] = c.im;
+        });
+    frame
+}
+
+/// The phase `atan2(q, i)` of each time step of a `(n, 2)` frame, in
+/// `(-pi, pi]`.
+pub fn phase(frame: ArrayView2<f64>) -> Array1<f64> {
+    assert_eq!(frame.ncols(), 2, "frames must have an I and a Q column");
+    frame.outer_iter().map(|iq| iq[1].atan2(iq[0])).collect()
+}
+
+/// The magnitude `sqrt(i^2 + q^2)` of each time step of a `(n, 2)` frame,
+/// i.e. its envelope.
+pub fn magnitude(frame: ArrayView2<f64>) -> Array1<f64> {
+    assert_eq!(frame.ncols(), 2, "frames must have an I and a Q column");
+    frame.outer_iter().map(|iq| iq[0].hypot(iq[1])).collect()
+}
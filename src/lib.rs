@@ -1,5 +1,17 @@
-// pub mod old_reader;
+pub mod augment;
+pub mod classify;
+pub mod dataset2016;
+pub mod dsp;
+pub mod features;
+pub mod filter;
+pub mod iq;
+pub mod metrics;
+#[cfg(feature = "pca")]
+pub mod pca;
+pub mod quality;
 pub mod reader;
+pub mod spectrum;
+pub mod window;
 
 // use pyo3::prelude::*;
 
@@ -1,19 +1,84 @@
-use radio_ml::reader;
-
-fn main() {
-    println!("Getting Started ...");
-    let folders_from_root = [
-        "Users",
-        "najibishaq",
-        "Documents",
-        "research",
-        "data",
-        "rf_terry",
-    ];
-
-    let num_samples = 100;
-    let samples = reader::RadioData::read(&folders_from_root, num_samples).join();
-    println!("Joined samples together into shape {:?}", samples.shape());
-
-    println!("Success!")
+use std::path::PathBuf;
+
+use clap::Parser;
+use ndarray::prelude::*;
+use radio_ml::reader::{self, ModulationMode, RadioData};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+/// Samples frames from the RadioML HDF5 files and writes them to `.npy`.
+#[derive(Debug, Parser)]
+struct Args {
+    /// Directory holding the `.h5` file of each modulation mode.
+    #[arg(long)]
+    data_dir: PathBuf,
+
+    /// Frames to sample from each SNR level of each mode.
+    #[arg(long, default_value_t = 100)]
+    num_samples: usize,
+
+    /// Comma-separated modes to read, e.g. `QPSK,QAM_16`. Reads all modes if
+    /// not given.
+    #[arg(long, value_delimiter = ',')]
+    modes: Vec<ModulationMode>,
+
+    /// Seed for sampling and shuffling the frames.
+    #[arg(long, default_value_t = reader::DEFAULT_SEED)]
+    seed: u64,
+
+    /// Shuffle the frames, and their labels, before writing.
+    #[arg(long)]
+    shuffle: bool,
+
+    /// Where to write the `(n_frames, 1024, 2)` frames.
+    #[arg(long)]
+    output: PathBuf,
+
+    /// Where to also write the label of each frame, as `int64`.
+    #[arg(long)]
+    labels: Option<PathBuf>,
+}
+
+fn main() -> Result<(), String> {
+    let args = Args::parse();
+
+    let modes = if args.modes.is_empty() {
+        ModulationMode::variants().to_vec()
+    } else {
+        args.modes
+    };
+    let handles = modes
+        .into_iter()
+        .map(|m| {
+            let path = args.data_dir.join(m.data_name());
+            hdf5::File::open(&path)
+                .map(|handle| (m, handle))
+                .map_err(|reason| format!("Could not open {:?} because {}", path, reason))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let data = RadioData::from_handles_seeded(handles, args.num_samples, args.seed, false)?;
+    let mut labels = data.labels();
+    let mut frames = data.join();
+
+    if args.shuffle {
+        let mut order = (0..labels.len()).collect::<Vec<_>>();
+        order.shuffle(&mut ChaCha8Rng::seed_from_u64(args.seed));
+        frames = frames.select(Axis(0), &order);
+        labels = labels.select(Axis(0), &order);
+    }
+
+    ndarray_npy::write_npy(&args.output, &frames)
+        .map_err(|reason| format!("Could not write {:?} because {}", args.output, reason))?;
+    if let Some(path) = &args.labels {
+        ndarray_npy::write_npy(path, &labels.mapv(|l| l as i64))
+            .map_err(|reason| format!("Could not write {:?} because {}", path, reason))?;
+    }
+
+    println!(
+        "Wrote frames of shape {:?} to {:?}",
+        frames.shape(),
+        args.output
+    );
+    Ok(())
 }
@@ -0,0 +1,48 @@
+/// Global constraints on which cells `(i, j)` of a dynamic programming
+/// matrix between `x` and `y` may be visited, shared by the elastic
+/// metrics.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Band {
+    None,
+    /// Cells with `i` at most `width` steps from the diagonal, which is
+    /// scaled to join the corners when the lengths differ.
+    SakoeChiba {
+        width: usize,
+    },
+    /// Cells in the parallelogram where the path's slope stays within
+    /// `[1 / max_slope, max_slope]` relative to the diagonal.
+    Itakura {
+        max_slope: f64,
+    },
+}
+
+impl Band {
+    /// The inclusive range of `x` indices allowed in row `j` of the matrix.
+    /// The range is empty, with `lo > hi`, when no cell of the row is
+    /// allowed.
+    pub(crate) fn row_bounds(&self, j: usize, x_len: usize, y_len: usize) -> (usize, usize) {
+        match *self {
+            Self::None => (0, x_len.saturating_sub(1)),
+            _ if x_len <= 1 || y_len <= 1 => (0, x_len.saturating_sub(1)),
+            Self::SakoeChiba { width } => {
+                let scale = (x_len - 1) as f64 / (y_len - 1) as f64;
+                let center = (j as f64 * scale).round() as usize;
+                (
+                    center.saturating_sub(width),
+                    (center + width).min(x_len - 1),
+                )
+            }
+            Self::Itakura { max_slope } => {
+                // Work in coordinates scaled to [0, 1] so that the diagonal
+                // has slope 1 regardless of the sequence lengths.
+                let v = j as f64 / (y_len - 1) as f64;
+                let lo = (v / max_slope).max(1. - max_slope * (1. - v));
+                let hi = (v * max_slope).min(1. - (1. - v) / max_slope);
+                let scale = (x_len - 1) as f64;
+                let lo = (lo * scale - 1e-9).ceil().max(0.) as usize;
+                let hi = ((hi * scale + 1e-9).floor() as usize).min(x_len - 1);
+                (lo, hi)
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+/// Euclidean distance between two frames of interleaved IQ samples,
+/// i.e. `[i_0, q_0, i_1, q_1, ...]`.
+///
+/// This is the L2 norm of the complex difference signal.
+#[derive(Debug, Default)]
+pub struct ComplexEuclidean<U: Number> {
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> ComplexEuclidean<U> {
+    pub fn new() -> Self {
+        Self { _u: PhantomData }
+    }
+
+    /// Always true, since this is a norm of the difference.
+    pub fn is_metric(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for ComplexEuclidean<U> {
+    fn name(&self) -> String {
+        "complex_euclidean".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len(), y.len(), "frames must have the same length");
+        assert_eq!(x.len() % 2, 0, "frames must hold interleaved IQ pairs");
+
+        let squared = x
+            .chunks_exact(2)
+            .zip(y.chunks_exact(2))
+            .map(|(a, b)| {
+                let di = a[0].as_f64() - b[0].as_f64();
+                let dq = a[1].as_f64() - b[1].as_f64();
+                di * di + dq * dq
+            })
+            .sum::<f64>();
+        U::from(squared.sqrt()).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+/// Manhattan distance between two frames of interleaved IQ samples, the sum
+/// of `|i_x - i_y| + |q_x - q_y|` over time steps.
+///
+/// This is the L1 counterpart of `ComplexEuclidean`, cheaper and less
+/// sensitive to outlying samples.
+#[derive(Debug, Default)]
+pub struct ComplexManhattan<U: Number> {
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> ComplexManhattan<U> {
+    pub fn new() -> Self {
+        Self { _u: PhantomData }
+    }
+
+    /// Always true, since this is a norm of the difference.
+    pub fn is_metric(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for ComplexManhattan<U> {
+    fn name(&self) -> String {
+        "complex_manhattan".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len(), y.len(), "frames must have the same length");
+        assert_eq!(x.len() % 2, 0, "frames must hold interleaved IQ pairs");
+
+        let distance = x
+            .chunks_exact(2)
+            .zip(y.chunks_exact(2))
+            .map(|(a, b)| {
+                let di = a[0].as_f64() - b[0].as_f64();
+                let dq = a[1].as_f64() - b[1].as_f64();
+                di.abs() + dq.abs()
+            })
+            .sum::<f64>();
+        U::from(distance).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manhattan_is_zero_for_identical_frames() {
+        let x = [0.5, -1., 2., 0.25];
+        let distance: f64 = ComplexManhattan::new().one_to_one(&x, &x);
+        assert_eq!(distance, 0.);
+    }
+
+    #[test]
+    fn manhattan_sums_i_and_q_differences() {
+        // Differences of (3, -4) and (-1, 0.5) in the two time steps.
+        let (x, y) = ([1., 2., 0., 0.5], [-2., 6., 1., 0.]);
+        let manhattan: f64 = ComplexManhattan::new().one_to_one(&x, &y);
+        assert_eq!(manhattan, 8.5);
+        let euclidean: f64 = ComplexEuclidean::new().one_to_one(&x, &y);
+        assert_eq!(euclidean, 26.25_f64.sqrt());
+    }
+}
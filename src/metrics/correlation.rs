@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+/// Pearson correlation distance, `1 - corr(x, y)`, in `[0, 2]`.
+///
+/// If either sequence is constant the correlation is undefined, and the
+/// maximum distance of 2 is returned.
+#[derive(Debug, Default)]
+pub struct CorrelationDistance<U: Number> {
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> CorrelationDistance<U> {
+    pub fn new() -> Self {
+        Self { _u: PhantomData }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for CorrelationDistance<U> {
+    fn name(&self) -> String {
+        "correlation".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len(), y.len(), "sequences must have the same length");
+
+        let mean = |v: &[T]| v.iter().map(|a| a.as_f64()).sum::<f64>() / v.len() as f64;
+        let (x_mean, y_mean) = (mean(x), mean(y));
+
+        let (mut xy, mut xx, mut yy) = (0., 0., 0.);
+        for (a, b) in x.iter().zip(y.iter()) {
+            let (a, b) = (a.as_f64() - x_mean, b.as_f64() - y_mean);
+            xy += a * b;
+            xx += a * a;
+            yy += b * b;
+        }
+
+        let distance = if xx == 0. || yy == 0. {
+            2.
+        } else {
+            1. - xy / (xx * yy).sqrt()
+        };
+        U::from(distance).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
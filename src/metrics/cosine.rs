@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+/// Cosine distance, `1 - (x . y) / (|x| |y|)`, in `[0, 2]`.
+///
+/// If either input has zero norm the angle is undefined, and a distance of
+/// 1 is returned.
+#[derive(Debug, Default)]
+pub struct CosineDistance<U: Number> {
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> CosineDistance<U> {
+    pub fn new() -> Self {
+        Self { _u: PhantomData }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for CosineDistance<U> {
+    fn name(&self) -> String {
+        "cosine".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len(), y.len(), "slices must have the same length");
+
+        let (mut xy, mut xx, mut yy) = (0., 0., 0.);
+        for (a, b) in x.iter().zip(y.iter()) {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            xy += a * b;
+            xx += a * a;
+            yy += b * b;
+        }
+
+        let distance = if xx == 0. || yy == 0. {
+            1.
+        } else {
+            1. - xy / (xx * yy).sqrt()
+        };
+        U::from(distance).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(x: &[f64], y: &[f64]) -> f64 {
+        CosineDistance::new().one_to_one(x, y)
+    }
+
+    #[test]
+    fn measures_the_angle_between_frames() {
+        let x = [1., 2., 2., 0.];
+        assert_eq!(distance(&x, &x), 0.);
+        assert_eq!(distance(&x, &[2., 4., 4., 0.]), 0.);
+        assert_eq!(distance(&[1., 0.], &[0., 1.]), 1.);
+        assert_eq!(distance(&x, &[-1., -2., -2., 0.]), 2.);
+    }
+
+    #[test]
+    fn zero_frames_are_at_distance_one() {
+        assert_eq!(distance(&[0., 0.], &[1., 2.]), 1.);
+        assert_eq!(distance(&[0., 0.], &[0., 0.]), 1.);
+    }
+}
@@ -0,0 +1,632 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use clam::{Metric, Number};
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+use super::band::Band;
+use crate::iq;
+
+/// Dynamic Time Warping distance between two sequences.
+///
+/// The `child_metric` measures the distance between individual time steps
+/// and the accumulated cost of the cheapest warping path is returned.
+///
+/// The child is held in an `Arc`, so one instance can be shared, and a
+/// `DynamicTimeWarping` used, across rayon threads without cloning it.
+#[derive(Debug)]
+pub struct DynamicTimeWarping<T: Number, U: Number> {
+    child_metric: Arc<dyn Metric<T, U> + Send + Sync>,
+    preprocess: Preprocess,
+    band: Band,
+    weights: StepWeights,
+}
+
+/// Multipliers on the point distance for each kind of step into a cell.
+#[derive(Debug, Clone, Copy)]
+struct StepWeights {
+    diagonal: f64,
+    /// Advancing only along `x`.
+    horizontal: f64,
+    /// Advancing only along `y`.
+    vertical: f64,
+}
+
+impl StepWeights {
+    const SYMMETRIC: Self = Self {
+        diagonal: 1.,
+        horizontal: 1.,
+        vertical: 1.,
+    };
+}
+
+/// Transformations applied to each sequence before warping.
+#[derive(Debug, Clone, Copy)]
+enum Preprocess {
+    None,
+    Derivative,
+    ZNormalize,
+}
+
+impl<T: Number, U: Number> DynamicTimeWarping<T, U> {
+    pub fn new(child_metric: Arc<dyn Metric<T, U> + Send + Sync>) -> Self {
+        Self {
+            child_metric,
+            preprocess: Preprocess::None,
+            band: Band::None,
+            weights: StepWeights::SYMMETRIC,
+        }
+    }
+
+    /// DTW with a weighted step pattern. The point distance of a cell is
+    /// multiplied by the weight of the step taken into it before the
+    /// candidate paths are compared, so weights above 1 penalize that kind
+    /// of step. `new` uses weights of 1 for all three steps.
+    pub fn with_steps(
+        child_metric: Arc<dyn Metric<T, U> + Send + Sync>,
+        diagonal_weight: f64,
+        horizontal_weight: f64,
+        vertical_weight: f64,
+    ) -> Self {
+        assert!(
+            [diagonal_weight, horizontal_weight, vertical_weight]
+                .iter()
+                .all(|&w| w.is_finite() && w > 0.),
+            "step weights must be positive and finite"
+        );
+        Self {
+            weights: StepWeights {
+                diagonal: diagonal_weight,
+                horizontal: horizontal_weight,
+                vertical: vertical_weight,
+            },
+            ..Self::new(child_metric)
+        }
+    }
+
+    /// DTW restricted to the Itakura parallelogram, so the warping path's
+    /// slope stays within `[1 / max_slope, max_slope]` relative to the
+    /// diagonal. Cells outside the parallelogram are never visited.
+    ///
+    /// The parallelogram is laid out with both sequences scaled to the same
+    /// length, so it joins the corners of the cost matrix whatever the real
+    /// lengths are and the slope is relative to the diagonal between them.
+    pub fn with_itakura(child_metric: Arc<dyn Metric<T, U> + Send + Sync>, max_slope: f64) -> Self {
+        assert!(
+            max_slope >= 1.,
+            "max_slope must be at least 1, got {max_slope}"
+        );
+        Self {
+            band: Band::Itakura { max_slope },
+            ..Self::new(child_metric)
+        }
+    }
+
+    /// DTW restricted to a Sakoe-Chiba band, so the warping path stays
+    /// within `width` steps of the diagonal. Cells outside the band are
+    /// never visited.
+    pub fn with_sakoe_chiba(
+        child_metric: Arc<dyn Metric<T, U> + Send + Sync>,
+        width: usize,
+    ) -> Self {
+        Self {
+            band: Band::SakoeChiba { width },
+            ..Self::new(child_metric)
+        }
+    }
+
+    /// Restricts this DTW to a Sakoe-Chiba band of `width` for use in an
+    /// index such as CLAM's, which prunes with the triangle inequality.
+    ///
+    /// DTW violates the triangle inequality, so a tree built on it can
+    /// silently miss true neighbors. Narrowing the band limits how far the
+    /// warping can stray, which makes violations rarer, and at `width` 0 on
+    /// equal-length sequences no warping is left: the distance is the sum
+    /// of the child distances along the diagonal, a metric whenever the
+    /// child metric is. Wider bands keep more of DTW's tolerance to timing
+    /// differences at the cost of more violations, which
+    /// `check_metric_properties` can measure on a sample of the data.
+    pub fn as_indexable(mut self, width: usize) -> Self {
+        self.band = Band::SakoeChiba { width };
+        self
+    }
+
+    /// Whether this configuration satisfies the metric axioms, assuming the
+    /// child metric does and the sequences have equal lengths. Only a band
+    /// of width 0 without preprocessing does; see `as_indexable`.
+    pub fn is_metric(&self) -> bool {
+        matches!(self.band, Band::SakoeChiba { width: 0 })
+            && matches!(self.preprocess, Preprocess::None)
+    }
+
+    /// Derivative DTW (Keogh & Pazzani, 2001).
+    ///
+    /// Sequences are replaced by estimates of their first derivative before
+    /// warping, so alignment follows the shape of the signal rather than its
+    /// offset. Callers still pass raw sequences. The derivatives can be
+    /// negative and fractional, so `T` should be a floating point type.
+    pub fn derivative(child_metric: Arc<dyn Metric<T, U> + Send + Sync>) -> Self {
+        Self {
+            preprocess: Preprocess::Derivative,
+            ..Self::new(child_metric)
+        }
+    }
+
+    /// DTW on z-normalized sequences, so that differences in amplitude
+    /// scale and offset between the inputs do not affect the distance.
+    ///
+    /// Each sequence has its mean subtracted and is divided by its standard
+    /// deviation. Constant sequences are only mean-subtracted. As with
+    /// `derivative`, `T` should be a floating point type.
+    pub fn z_normalized(child_metric: Arc<dyn Metric<T, U> + Send + Sync>) -> Self {
+        Self {
+            preprocess: Preprocess::ZNormalize,
+            ..Self::new(child_metric)
+        }
+    }
+
+    /// Applies any preprocessing this variant performs on the raw sequence.
+    pub(crate) fn prepare<'a>(&self, x: &'a [T]) -> Cow<'a, [T]> {
+        match self.preprocess {
+            Preprocess::None => Cow::Borrowed(x),
+            Preprocess::Derivative => Cow::Owned(central_difference(x)),
+            Preprocess::ZNormalize => Cow::Owned(z_normalize(x)),
+        }
+    }
+
+    /// DTW over multivariate sequences, e.g. frames of shape `(1024, 2)`.
+    ///
+    /// Each row is one time step and the child metric is called on the full
+    /// feature vector, so I and Q are warped jointly.
+    pub fn one_to_one_multi(&self, x: ArrayView2<T>, y: ArrayView2<T>) -> U {
+        assert_eq!(
+            x.ncols(),
+            y.ncols(),
+            "sequences must have the same number of features"
+        );
+        let x_rows = x.outer_iter().map(|r| r.to_vec()).collect::<Vec<_>>();
+        let y_rows = y.outer_iter().map(|r| r.to_vec()).collect::<Vec<_>>();
+        self.accumulate(x_rows.len(), y_rows.len(), |i, j| {
+            self.child_metric.one_to_one(&x_rows[i], &y_rows[j])
+        })
+        .0
+    }
+
+    /// DTW between the envelopes of two `(n, 2)` IQ frames, as computed by
+    /// `iq::magnitude`, so that frames differing only in phase are close.
+    ///
+    /// For integer `T` the magnitudes are truncated, and saturate at
+    /// `T::max_value()`.
+    pub fn one_to_one_envelope(&self, x: ArrayView2<T>, y: ArrayView2<T>) -> U {
+        let envelope = |frame: ArrayView2<T>| {
+            iq::magnitude(frame.mapv(|v| v.as_f64()).view())
+                .iter()
+                .map(|&m| T::from(m).unwrap_or_else(T::max_value))
+                .collect::<Vec<_>>()
+        };
+        self.one_to_one(&envelope(x), &envelope(y))
+    }
+
+    /// DTW within a Sakoe-Chiba band of `width`, regardless of the
+    /// configured constraint, that gives up with `None` once the distance
+    /// is known to exceed `cutoff`. `x` and `y` must already be prepared.
+    pub(crate) fn banded_with_cutoff(
+        &self,
+        x: &[T],
+        y: &[T],
+        width: usize,
+        cutoff: U,
+    ) -> Option<U> {
+        let band = Band::SakoeChiba { width };
+        let (cost, _) = self.accumulate_within(
+            band,
+            x.len(),
+            y.len(),
+            cutoff,
+            |i, j| self.child_metric.one_to_one(&x[i..=i], &y[j..=j]),
+            |_, _| (),
+        );
+        (cost <= cutoff).then_some(cost)
+    }
+
+    /// The accumulated cost matrix that `one_to_one` fills, with row `j`
+    /// and column `i` holding the cheapest cost of aligning `x[..=i]` with
+    /// `y[..=j]`, so the shape is `(y.len(), x.len())` and the distance is
+    /// the last entry. Cells outside the band are `U::max_value()`.
+    ///
+    /// Unlike `one_to_one`, this keeps every row in memory.
+    pub fn cost_matrix(&self, x: &[T], y: &[T]) -> Array2<U> {
+        let (x, y) = (self.prepare(x), self.prepare(y));
+        let mut matrix = Array2::from_elem((y.len(), x.len()), U::max_value());
+        self.accumulate_within(
+            self.band,
+            x.len(),
+            y.len(),
+            U::max_value(),
+            |i, j| self.child_metric.one_to_one(&x[i..=i], &y[j..=j]),
+            |j, row| {
+                matrix
+                    .row_mut(j)
+                    .iter_mut()
+                    .zip(row)
+                    .for_each(|(cell, &(cost, _))| *cell = cost)
+            },
+        );
+        matrix
+    }
+
+    /// Like `one_to_one` but divides the accumulated cost by the number of
+    /// steps in the optimal warping path, so that costs are comparable
+    /// across sequences of different lengths.
+    pub fn one_to_one_normalized(&self, x: &[T], y: &[T]) -> U {
+        let (x, y) = (self.prepare(x), self.prepare(y));
+        let (cost, path_len) = self.accumulate(x.len(), y.len(), |i, j| {
+            self.child_metric.one_to_one(&x[i..=i], &y[j..=j])
+        });
+        if path_len == 0 {
+            cost
+        } else {
+            // Divide in f64, since the path can be longer than a narrow
+            // integer `U` can hold.
+            U::from(cost.as_f64() / path_len as f64).unwrap_or_else(U::max_value)
+        }
+    }
+
+    /// The path-normalized cost `d` from `one_to_one_normalized`, mapped to
+    /// `[0, 1)` by `1 - exp(-d / max_expected)`, for combining DTW with
+    /// bounded similarities such as cosine or correlation.
+    ///
+    /// Identical sequences give 0, a cost of `max_expected` gives
+    /// `1 - 1/e` (about 0.63), and the output approaches 1 as the cost grows.
+    /// The transform is monotonic, so it ranks pairs as the normalized cost
+    /// does, and it is symmetric in `x` and `y` whenever that cost is, as
+    /// with the default step weights. `max_expected` must be positive.
+    pub fn one_to_one_bounded(&self, x: &[T], y: &[T], max_expected: U) -> f64 {
+        let scale = max_expected.as_f64();
+        assert!(scale > 0., "max_expected must be positive, got {scale}");
+        let cost = self.one_to_one_normalized(x, y).as_f64();
+        1. - (-cost / scale).exp()
+    }
+
+    /// Fills the cost matrix one row at a time, keeping only the previous
+    /// and current rows in memory. `distance(i, j)` is the cost of matching
+    /// `x[i]` with `y[j]`.
+    ///
+    /// Returns the accumulated cost along with the length of the path that
+    /// achieved it. Costs saturate at `U::max_value()` instead of wrapping,
+    /// which matters for narrow integer `U` on 1024-length frames.
+    fn accumulate<F>(&self, x_len: usize, y_len: usize, distance: F) -> (U, usize)
+    where
+        F: Fn(usize, usize) -> U,
+    {
+        self.accumulate_within(self.band, x_len, y_len, U::max_value(), distance, |_, _| ())
+    }
+
+    /// Like `accumulate`, but within `band` instead of the configured one,
+    /// and abandoning the fill as soon as every cell of a row costs more
+    /// than `cutoff`, in which case `(U::max_value(), 0)` is returned. Since
+    /// costs only grow along a path, no path can then finish under `cutoff`.
+    ///
+    /// `on_row(j, row)` is called with each row once it is filled.
+    fn accumulate_within<F, R>(
+        &self,
+        band: Band,
+        x_len: usize,
+        y_len: usize,
+        cutoff: U,
+        distance: F,
+        mut on_row: R,
+    ) -> (U, usize)
+    where
+        F: Fn(usize, usize) -> U,
+        R: FnMut(usize, &[(U, usize)]),
+    {
+        if x_len == 0 || y_len == 0 {
+            return if x_len == y_len {
+                (U::zero(), 0)
+            } else {
+                (U::max_value(), 0)
+            };
+        }
+
+        let mut previous = vec![(U::zero(), 0); x_len];
+        let mut current = vec![(U::zero(), 0); x_len];
+
+        for j in 0..y_len {
+            let (lo, hi) = band.row_bounds(j, x_len, y_len);
+            for i in 0..x_len {
+                if i < lo || i > hi {
+                    current[i] = (U::max_value(), 0);
+                    continue;
+                }
+                let d = distance(i, j);
+                let horizontal = |cell| extend(cell, self.weighted(d, self.weights.horizontal));
+                let vertical = |cell| extend(cell, self.weighted(d, self.weights.vertical));
+                current[i] = match (i, j) {
+                    (0, 0) => (d, 1),
+                    (0, _) => vertical(previous[0]),
+                    (_, 0) => horizontal(current[i - 1]),
+                    _ => min3(
+                        extend(previous[i - 1], self.weighted(d, self.weights.diagonal)),
+                        vertical(previous[i]),
+                        horizontal(current[i - 1]),
+                    ),
+                };
+            }
+            on_row(j, &current);
+            if current.iter().all(|&(cost, _)| cost > cutoff) {
+                return (U::max_value(), 0);
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[x_len - 1]
+    }
+
+    /// `distance * weight`, saturating at `U::max_value()` like the
+    /// accumulated costs do.
+    fn weighted(&self, distance: U, weight: f64) -> U {
+        if weight == 1. {
+            distance
+        } else {
+            let weighted = (distance.as_f64() * weight).min(U::max_value().as_f64());
+            U::from(weighted).unwrap_or_else(U::max_value)
+        }
+    }
+}
+
+/// Central difference estimate `(x[i+1] - x[i-1]) / 2` of the derivative,
+/// with one-sided differences at the endpoints.
+fn central_difference<T: Number>(x: &[T]) -> Vec<T> {
+    let n = x.len();
+    if n < 2 {
+        return vec![T::zero(); n];
+    }
+    let x = x.iter().map(|v| v.as_f64()).collect::<Vec<_>>();
+    (0..n)
+        .map(|i| match i {
+            0 => x[1] - x[0],
+            _ if i == n - 1 => x[n - 1] - x[n - 2],
+            _ => (x[i + 1] - x[i - 1]) / 2.,
+        })
+        .map(|d| T::from(d).unwrap())
+        .collect()
+}
+
+/// `a + b`, clamped to `U::max_value()` instead of overflowing.
+fn saturating_add<U: Number>(a: U, b: U) -> U {
+    if b > U::max_value() - a {
+        U::max_value()
+    } else {
+        a + b
+    }
+}
+
+/// `(x - mean) / std`, or just `x - mean` if `x` is constant.
+fn z_normalize<T: Number>(x: &[T]) -> Vec<T> {
+    if x.is_empty() {
+        return Vec::new();
+    }
+    let x = x.iter().map(|v| v.as_f64()).collect::<Vec<_>>();
+    let n = x.len() as f64;
+    let mean = x.iter().sum::<f64>() / n;
+    let std = (x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let std = if std > 0. { std } else { 1. };
+    x.iter()
+        .map(|v| T::from((v - mean) / std).unwrap())
+        .collect()
+}
+
+/// Steps from `cell` into a cell with point cost `distance`.
+fn extend<U: Number>((cost, steps): (U, usize), distance: U) -> (U, usize) {
+    (saturating_add(distance, cost), steps + 1)
+}
+
+/// The cheapest of three `(cost, path_length)` cells, preferring the
+/// diagonal on ties.
+fn min3<U: Number>(diagonal: (U, usize), up: (U, usize), left: (U, usize)) -> (U, usize) {
+    let best = if up.0 < diagonal.0 { up } else { diagonal };
+    if left.0 < best.0 {
+        left
+    } else {
+        best
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for DynamicTimeWarping<T, U> {
+    fn name(&self) -> String {
+        format!("dtw_{}", self.child_metric.name())
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        let (x, y) = (self.prepare(x), self.prepare(y));
+        self.accumulate(x.len(), y.len(), |i, j| {
+            self.child_metric.one_to_one(&x[i..=i], &y[j..=j])
+        })
+        .0
+    }
+
+    /// Computes the distances to all candidates in parallel. The output is
+    /// in the same order as `candidates`.
+    fn one_to_many(&self, query: &[T], candidates: &[&[T]]) -> Vec<U> {
+        candidates
+            .par_iter()
+            .map(|&candidate| self.one_to_one(query, candidate))
+            .collect()
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Minkowski;
+
+    fn euclidean<U: Number>() -> Arc<dyn Metric<f64, U> + Send + Sync> {
+        Arc::new(Minkowski::new(2.))
+    }
+
+    #[test]
+    fn heavier_diagonal_weights_push_the_path_off_the_diagonal() {
+        let (x, y) = ([0.; 4], [1.; 4]);
+        let path_len = |dtw: &DynamicTimeWarping<f64, f64>| {
+            dtw.accumulate(x.len(), y.len(), |i, j| {
+                dtw.child_metric.one_to_one(&x[i..=i], &y[j..=j])
+            })
+        };
+
+        // With equal weights the cheapest path is the diagonal.
+        assert_eq!(path_len(&DynamicTimeWarping::new(euclidean())), (4., 4));
+        // With a heavy diagonal it only takes horizontal and vertical steps,
+        // along the edges of the cost matrix.
+        let heavy = DynamicTimeWarping::with_steps(euclidean(), 10., 1., 1.);
+        assert_eq!(path_len(&heavy), (7., 7));
+        assert!(heavy.one_to_one(&x, &y) > DynamicTimeWarping::new(euclidean()).one_to_one(&x, &y));
+    }
+
+    #[test]
+    fn weighted_steps_saturate_for_small_u() {
+        let dtw = DynamicTimeWarping::<f64, u8>::with_steps(euclidean(), 2., 1., 1.);
+        assert_eq!(dtw.one_to_one(&[0., 0.], &[200., 200.]), u8::MAX);
+    }
+
+    #[test]
+    fn shares_one_instance_across_threads() {
+        let dtw = DynamicTimeWarping::<f64, f64>::new(euclidean());
+        let frames = (0..16)
+            .map(|k| {
+                (0..32)
+                    .map(|i| ((i + k) as f64 / 4.).sin())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let sequential = frames
+            .iter()
+            .map(|f| dtw.one_to_one(&frames[0], f))
+            .collect::<Vec<_>>();
+        let parallel = frames
+            .par_iter()
+            .map(|f| dtw.one_to_one(&frames[0], f))
+            .collect::<Vec<_>>();
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn normalized_cost_is_zero_for_identical_sequences() {
+        let dtw = DynamicTimeWarping::<f64, f64>::new(euclidean());
+        let x = [0.5, -1., 2., 3., 0.];
+        assert_eq!(dtw.one_to_one_normalized(&x, &x), 0.);
+    }
+
+    #[test]
+    fn normalized_cost_ignores_repeating_each_sample() {
+        let dtw = DynamicTimeWarping::<f64, f64>::new(euclidean());
+        let (x, y) = ([0., 2.], [1., 3.]);
+        let (x_padded, y_padded) = ([0., 0., 2., 2.], [1., 1., 3., 3.]);
+        assert_eq!(dtw.one_to_one(&x, &y), 2.);
+        assert_eq!(dtw.one_to_one(&x_padded, &y_padded), 4.);
+        assert_eq!(dtw.one_to_one_normalized(&x, &y), 1.);
+        assert_eq!(dtw.one_to_one_normalized(&x_padded, &y_padded), 1.);
+    }
+
+    #[test]
+    fn derivative_dtw_ignores_an_offset_ramp() {
+        let x = (0..16).map(f64::from).collect::<Vec<_>>();
+        let y = x.iter().map(|v| v + 5.).collect::<Vec<_>>();
+        let manhattan = || Arc::new(Minkowski::new(1.));
+        let plain = DynamicTimeWarping::<f64, f64>::new(manhattan()).one_to_one(&x, &y);
+        let derivative = DynamicTimeWarping::<f64, f64>::derivative(manhattan()).one_to_one(&x, &y);
+        assert_eq!(derivative, 0.);
+        assert!(derivative < plain);
+    }
+
+    #[test]
+    fn accumulated_cost_saturates_for_small_u() {
+        // 1024 steps of cost 1 would wrap a `u8` back around to 0.
+        let dtw = DynamicTimeWarping::<f64, u8>::new(euclidean());
+        assert_eq!(dtw.one_to_one(&[0.; 1024], &[1.; 1024]), u8::MAX);
+        assert_eq!(dtw.one_to_one(&[0.; 100], &[1.; 100]), 100);
+    }
+
+    #[test]
+    fn itakura_never_visits_cells_outside_the_parallelogram() {
+        let x = [0., 0., 0., 0., 0., 0., 0., 1.];
+        let y = [0., 1., 1., 1., 1., 1., 1., 1.];
+        let itakura = DynamicTimeWarping::<f64, f64>::with_itakura(euclidean(), 2.);
+
+        let matrix = itakura.cost_matrix(&x, &y);
+        for (j, row) in matrix.outer_iter().enumerate() {
+            let (lo, hi) = Band::Itakura { max_slope: 2. }.row_bounds(j, x.len(), y.len());
+            for (i, &cost) in row.iter().enumerate() {
+                assert_eq!(cost == f64::MAX, i < lo || i > hi, "cell ({i}, {j})");
+            }
+        }
+
+        // The free path runs along the edges of the matrix, outside the
+        // parallelogram, so the constrained distance must pay for warping
+        // less.
+        assert_eq!(DynamicTimeWarping::new(euclidean()).one_to_one(&x, &y), 0.);
+        assert!(itakura.one_to_one(&x, &y) > 0.);
+        assert_eq!(itakura.one_to_one(&x, &y), matrix[[7, 7]]);
+    }
+
+    #[test]
+    fn z_normalization_ignores_amplitude_scale() {
+        let dtw = DynamicTimeWarping::<f64, f64>::z_normalized(euclidean());
+        let x = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let y = (0..32).map(|i| (i as f64 / 3.).cos()).collect::<Vec<_>>();
+        let scaled = y.iter().map(|v| 5. * v).collect::<Vec<_>>();
+        let (d, d_scaled) = (dtw.one_to_one(&x, &y), dtw.one_to_one(&x, &scaled));
+        assert!((d - d_scaled).abs() < 1e-9, "{d} vs {d_scaled}");
+        assert!(DynamicTimeWarping::new(euclidean()).one_to_one(&x, &scaled) > d);
+    }
+
+    #[test]
+    fn z_normalization_handles_constant_sequences() {
+        let dtw = DynamicTimeWarping::<f64, f64>::z_normalized(euclidean());
+        assert_eq!(dtw.one_to_one(&[3.; 8], &[-1.; 8]), 0.);
+        assert!(dtw.one_to_one(&[3.; 8], &[0., 1., 0., 1.]).is_finite());
+    }
+
+    #[test]
+    fn envelopes_ignore_a_phase_offset() {
+        let frame = |phase: f64| {
+            Array2::from_shape_fn((64, 2), |(t, c)| {
+                let amplitude = 1. + (t as f64 / 8.).sin() / 2.;
+                let angle = t as f64 / 5. + phase;
+                amplitude * if c == 0 { angle.cos() } else { angle.sin() }
+            })
+        };
+        let (x, y) = (frame(0.), frame(2.));
+        let dtw = DynamicTimeWarping::<f64, f64>::new(euclidean());
+        assert!(dtw.one_to_one_envelope(x.view(), y.view()) < 1e-9);
+        assert!(dtw.one_to_one_multi(x.view(), y.view()) > 1.);
+    }
+
+    #[test]
+    fn normalized_cost_handles_paths_longer_than_u() {
+        // The 1024-step path does not fit in a `u8`.
+        let dtw = DynamicTimeWarping::<f64, u8>::new(euclidean());
+        let x = [0.; 1024];
+        assert_eq!(dtw.one_to_one_normalized(&x, &x), 0);
+        let mut y = [0.; 1024];
+        y[..512].fill(1.);
+        assert_eq!(dtw.one_to_one(&x, &y), u8::MAX);
+        assert_eq!(dtw.one_to_one_normalized(&x, &y), 0);
+
+        let dtw = DynamicTimeWarping::<f64, u16>::new(euclidean());
+        assert_eq!(dtw.one_to_one_normalized(&[0.; 1024], &[3.; 1024]), 3);
+    }
+
+    #[test]
+    fn integer_envelopes_saturate() {
+        // The magnitude of (255, 255) does not fit in a `u8`.
+        let dtw = DynamicTimeWarping::<u8, f64>::new(Arc::new(Minkowski::new(1.)));
+        let (x, y) = (array![[255, 255], [3, 4]], array![[255, 0], [0, 5]]);
+        assert_eq!(dtw.one_to_one_envelope(x.view(), y.view()), 0.);
+    }
+}
@@ -0,0 +1,125 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+use super::band::Band;
+
+/// Edit Distance on Real sequences (Chen et al., 2005).
+///
+/// Substituting one point for another costs 0 if they are within `epsilon`
+/// of each other and 1 otherwise, and every insertion or deletion costs 1.
+/// The distance is the minimal total edit cost.
+#[derive(Debug)]
+pub struct Edr<T: Number, U: Number> {
+    epsilon: T,
+    band: Band,
+    _u: PhantomData<U>,
+}
+
+impl<T: Number, U: Number> Edr<T, U> {
+    pub fn new(epsilon: T) -> Self {
+        Self {
+            epsilon,
+            band: Band::None,
+            _u: PhantomData,
+        }
+    }
+
+    /// EDR that only fills the cells of the DP within `width` steps of the
+    /// diagonal, as in a Sakoe-Chiba band, treating the rest as unreachable.
+    pub fn with_band(epsilon: T, width: usize) -> Self {
+        Self {
+            band: Band::SakoeChiba { width },
+            ..Self::new(epsilon)
+        }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Edr<T, U> {
+    fn name(&self) -> String {
+        "edr".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        // `previous[i]` holds the edit cost between `x[..i]` and `y[..j]`.
+        let mut previous = (0..=x.len()).collect::<Vec<_>>();
+        let mut current = vec![0_usize; x.len() + 1];
+
+        for (j, &b) in y.iter().enumerate() {
+            let (lo, hi) = self.band.row_bounds(j, x.len(), y.len());
+            current[0] = j + 1;
+            for (i, &a) in x.iter().enumerate() {
+                if i < lo || i > hi {
+                    current[i + 1] = usize::MAX;
+                    continue;
+                }
+                let substitution =
+                    usize::from((a.as_f64() - b.as_f64()).abs() > self.epsilon.as_f64());
+                current[i + 1] = previous[i]
+                    .saturating_add(substitution)
+                    .min(previous[i + 1].saturating_add(1))
+                    .min(current[i].saturating_add(1));
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        // A band can leave the corner unreachable, at `usize::MAX`.
+        match previous[x.len()] {
+            usize::MAX => U::max_value(),
+            cost => U::from(cost).unwrap_or_else(U::max_value),
+        }
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreachable_corner_saturates() {
+        // A band of width 0 skips columns when `x` is longer than `y`, so no
+        // path reaches the corner.
+        let edr = Edr::<f64, u8>::with_band(0.1, 0);
+        assert_eq!(edr.one_to_one(&[1.; 6], &[1.; 2]), u8::MAX);
+        let edr = Edr::<f64, f64>::with_band(0.1, 0);
+        assert_eq!(edr.one_to_one(&[1.; 6], &[1.; 2]), f64::MAX);
+        assert_eq!(Edr::<f64, u8>::new(0.1).one_to_one(&[1.; 6], &[1.; 2]), 4);
+    }
+
+    #[test]
+    fn narrow_band_matches_unbanded_near_the_diagonal() {
+        // `y` is `x` shifted by one step, so the best matching stays within
+        // a step of the diagonal.
+        let x = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let y = (0..32)
+            .map(|i| ((i + 1) as f64 / 4.).sin())
+            .collect::<Vec<_>>();
+        let (banded, unbanded) = (
+            Edr::<f64, f64>::with_band(0.1, 3),
+            Edr::<f64, f64>::new(0.1),
+        );
+        assert_eq!(banded.one_to_one(&x, &y), unbanded.one_to_one(&x, &y));
+    }
+
+    #[test]
+    fn identical_sequences_are_at_distance_zero() {
+        let x = [0.5, -1., 2., 3., 0.];
+        assert_eq!(Edr::<f64, u8>::new(0.1).one_to_one(&x, &x), 0);
+    }
+
+    #[test]
+    fn counts_edits_of_a_shifted_pair() {
+        let edr = Edr::<f64, u8>::new(0.1);
+        let x = [0., 1., 2., 3., 4.];
+        // Every point moves by less than `epsilon`, which is free.
+        assert_eq!(edr.one_to_one(&x, &[0.05, 1.05, 2.05, 3.05, 4.05]), 0);
+        // Only the point that moves further needs a substitution.
+        assert_eq!(edr.one_to_one(&x, &[0.05, 1.05, 2.5, 3.05, 4.05]), 1);
+        // A shift by one step deletes the first point and inserts the last.
+        assert_eq!(edr.one_to_one(&x, &[1.05, 2.05, 3.05, 4.05, 5.05]), 2);
+    }
+}
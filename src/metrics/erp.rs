@@ -0,0 +1,139 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+use super::band::Band;
+
+/// Edit distance with Real Penalty (Chen & Ng, 2004).
+///
+/// Matching two points costs `|x_i - y_j|`, and deleting `x_i` or inserting
+/// `y_j` costs its distance to the reference point `gap`. The distance is
+/// the minimal total cost. Unlike `Lcss` and `Edr`, ERP satisfies the
+/// triangle inequality, so it is a true metric and safe for CLAM's
+/// pruning.
+#[derive(Debug)]
+pub struct Erp<T: Number, U: Number> {
+    gap: T,
+    band: Band,
+    _u: PhantomData<U>,
+}
+
+impl<T: Number, U: Number> Erp<T, U> {
+    pub fn new(gap: T) -> Self {
+        Self {
+            gap,
+            band: Band::None,
+            _u: PhantomData,
+        }
+    }
+
+    /// Whether this configuration satisfies the metric axioms. Unbanded ERP
+    /// does, but a band can break the triangle inequality.
+    pub fn is_metric(&self) -> bool {
+        matches!(self.band, Band::None)
+    }
+
+    /// ERP that only fills the cells of the DP within `width` steps of the
+    /// diagonal, as in a Sakoe-Chiba band, treating the rest as unreachable.
+    pub fn with_band(gap: T, width: usize) -> Self {
+        Self {
+            band: Band::SakoeChiba { width },
+            ..Self::new(gap)
+        }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Erp<T, U> {
+    fn name(&self) -> String {
+        "erp".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        let gap = self.gap.as_f64();
+        let gap_cost = |a: T| (a.as_f64() - gap).abs();
+
+        // `previous[i]` holds the cost between `x[..i]` and `y[..j]`.
+        let mut previous = std::iter::once(0.)
+            .chain(x.iter().scan(0., |cost, &a| {
+                *cost += gap_cost(a);
+                Some(*cost)
+            }))
+            .collect::<Vec<_>>();
+        let mut current = vec![0.; x.len() + 1];
+
+        for (j, &b) in y.iter().enumerate() {
+            let (lo, hi) = self.band.row_bounds(j, x.len(), y.len());
+            current[0] = previous[0] + gap_cost(b);
+            for (i, &a) in x.iter().enumerate() {
+                if i < lo || i > hi {
+                    current[i + 1] = f64::INFINITY;
+                    continue;
+                }
+                let matched = previous[i] + (a.as_f64() - b.as_f64()).abs();
+                let deleted = current[i] + gap_cost(a);
+                let inserted = previous[i + 1] + gap_cost(b);
+                current[i + 1] = matched.min(deleted).min(inserted);
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        // A band can leave the corner unreachable, at infinite cost, and
+        // costs can exceed a narrow integer `U`, so both saturate.
+        let cost = previous[x.len()].min(U::max_value().as_f64());
+        U::from(cost).unwrap_or_else(U::max_value)
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreachable_corner_saturates() {
+        // A band of width 0 skips columns when `x` is longer than `y`, so no
+        // path reaches the corner.
+        let erp = Erp::<f64, u8>::with_band(0., 0);
+        assert_eq!(erp.one_to_one(&[1.; 6], &[1.; 2]), u8::MAX);
+        let erp = Erp::<f64, f64>::with_band(0., 0);
+        assert_eq!(erp.one_to_one(&[1.; 6], &[1.; 2]), f64::MAX);
+        let erp = Erp::<f64, u8>::new(0.);
+        assert_eq!(erp.one_to_one(&[200.], &[0., 200.]), 0);
+        assert_eq!(erp.one_to_one(&[200., 200.], &[0., 0.]), u8::MAX);
+    }
+
+    #[test]
+    fn narrow_band_matches_unbanded_near_the_diagonal() {
+        // `y` is `x` shifted by one step, so the best matching stays within
+        // a step of the diagonal.
+        let x = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let y = (0..32)
+            .map(|i| ((i + 1) as f64 / 4.).sin())
+            .collect::<Vec<_>>();
+        let (banded, unbanded) = (Erp::<f64, f64>::with_band(0., 3), Erp::<f64, f64>::new(0.));
+        assert_eq!(banded.one_to_one(&x, &y), unbanded.one_to_one(&x, &y));
+    }
+
+    #[test]
+    fn identical_sequences_are_at_distance_zero() {
+        let x = [0.5, -1., 2., 3., 0.];
+        assert_eq!(Erp::<f64, f64>::new(1.).one_to_one(&x, &x), 0.);
+    }
+
+    #[test]
+    fn matches_a_hand_computed_sum() {
+        // The cheapest edit matches 0 with 1 and 4 with 5, costing 1 each,
+        // and deletes the trailing 2, which costs nothing since it is the
+        // gap itself.
+        let erp = Erp::<f64, f64>::new(2.);
+        let (x, y) = ([0., 4., 2.], [1., 5.]);
+        assert_eq!(erp.one_to_one(&x, &y), 2.);
+        assert_eq!(erp.one_to_one(&y, &x), 2.);
+        // With a trailing 5 it is cheapest to match the 5s and delete the 4,
+        // which costs its distance of 2 to the gap.
+        assert_eq!(erp.one_to_one(&[0., 4., 5.], &y), 3.);
+    }
+}
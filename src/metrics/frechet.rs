@@ -0,0 +1,117 @@
+use clam::{Metric, Number};
+
+/// Discrete Fréchet distance (Eiter & Mannila, 1994) between two frames of
+/// interleaved IQ samples, treating each frame as a curve of complex points.
+///
+/// This uses the same recursion over time steps as `DynamicTimeWarping`,
+/// but a coupling is scored by the largest `child_metric` distance along it
+/// rather than by the sum, so a single bad match dominates the result. As in
+/// `Hausdorff`, the `child_metric` is called on `[i, q]` pairs.
+#[derive(Debug)]
+pub struct Frechet<T: Number, U: Number> {
+    child_metric: Box<dyn Metric<T, U>>,
+}
+
+impl<T: Number, U: Number> Frechet<T, U> {
+    pub fn new(child_metric: Box<dyn Metric<T, U>>) -> Self {
+        Self { child_metric }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Frechet<T, U> {
+    fn name(&self) -> String {
+        format!("frechet_{}", self.child_metric.name())
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len() % 2, 0, "frames must hold interleaved IQ pairs");
+        assert_eq!(y.len() % 2, 0, "frames must hold interleaved IQ pairs");
+        let x = x.chunks_exact(2).collect::<Vec<_>>();
+        let y = y.chunks_exact(2).collect::<Vec<_>>();
+
+        if x.is_empty() || y.is_empty() {
+            return if x.len() == y.len() {
+                U::zero()
+            } else {
+                U::max_value()
+            };
+        }
+
+        let min = |a: U, b: U| if a < b { a } else { b };
+        let max = |a: U, b: U| if a > b { a } else { b };
+
+        let mut previous = vec![U::zero(); x.len()];
+        let mut current = vec![U::zero(); x.len()];
+
+        for (j, b) in y.iter().enumerate() {
+            for (i, a) in x.iter().enumerate() {
+                let distance = self.child_metric.one_to_one(a, b);
+                current[i] = match (i, j) {
+                    (0, 0) => distance,
+                    (0, _) => max(distance, previous[0]),
+                    (_, 0) => max(distance, current[i - 1]),
+                    _ => max(
+                        distance,
+                        min(min(previous[i - 1], previous[i]), current[i - 1]),
+                    ),
+                };
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[x.len() - 1]
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::metrics::{ComplexEuclidean, DynamicTimeWarping, Minkowski};
+
+    /// Interleaved IQ samples of the points `(i, 0)` for each `i` in `is`.
+    fn real_curve(is: &[f64]) -> Vec<f64> {
+        is.iter().flat_map(|&i| [i, 0.]).collect()
+    }
+
+    fn frechet(x: &[f64], y: &[f64]) -> f64 {
+        Frechet::new(Box::new(ComplexEuclidean::new())).one_to_one(x, y)
+    }
+
+    /// DTW over the same points, for comparison.
+    fn dtw(x: &[f64], y: &[f64]) -> f64 {
+        let dtw = DynamicTimeWarping::<f64, f64>::new(Arc::new(Minkowski::new(2.)));
+        let as_points = |c: &[f64]| Array2::from_shape_vec((c.len() / 2, 2), c.to_vec()).unwrap();
+        dtw.one_to_one_multi(as_points(x).view(), as_points(y).view())
+    }
+
+    #[test]
+    fn matches_hand_computed_distances() {
+        let x = [0., 0., 3., 4.];
+        assert_eq!(frechet(&x, &x), 0.);
+        // Repeating a point costs nothing.
+        assert_eq!(frechet(&x, &[0., 0., 0., 0., 3., 4.]), 0.);
+        // The second points, (1, 0) and (4, 0), must be coupled.
+        assert_eq!(frechet(&real_curve(&[0., 1.]), &real_curve(&[0., 4.])), 3.);
+    }
+
+    #[test]
+    fn takes_the_max_where_dtw_takes_the_sum() {
+        let x = real_curve(&[0., 0., 0.]);
+        // A single bump costs the same either way.
+        let bump = real_curve(&[0., 1., 0.]);
+        assert_eq!(frechet(&x, &bump), 1.);
+        assert_eq!(dtw(&x, &bump), 1.);
+        // A wider one is paid for once by Frechet but at every step by DTW.
+        let plateau = real_curve(&[0., 1., 1.]);
+        assert_eq!(frechet(&x, &plateau), 1.);
+        assert_eq!(dtw(&x, &plateau), 2.);
+    }
+}
@@ -0,0 +1,69 @@
+use clam::{Metric, Number};
+
+/// Symmetric Hausdorff distance between two frames of interleaved IQ
+/// samples, treating each frame as a set of complex points.
+///
+/// The `child_metric` is called on `[i, q]` pairs to measure the distance
+/// between points.
+#[derive(Debug)]
+pub struct Hausdorff<T: Number, U: Number> {
+    child_metric: Box<dyn Metric<T, U>>,
+}
+
+impl<T: Number, U: Number> Hausdorff<T, U> {
+    pub fn new(child_metric: Box<dyn Metric<T, U>>) -> Self {
+        Self { child_metric }
+    }
+
+    /// The directed Hausdorff distance `sup_{a in x} inf_{b in y} d(a, b)`.
+    pub fn directed(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len() % 2, 0, "frames must hold interleaved IQ pairs");
+        assert_eq!(y.len() % 2, 0, "frames must hold interleaved IQ pairs");
+
+        x.chunks_exact(2)
+            .map(|a| {
+                y.chunks_exact(2)
+                    .map(|b| self.child_metric.one_to_one(a, b))
+                    .fold(U::max_value(), |min, d| if d < min { d } else { min })
+            })
+            .fold(U::zero(), |max, d| if d > max { d } else { max })
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Hausdorff<T, U> {
+    fn name(&self) -> String {
+        format!("hausdorff_{}", self.child_metric.name())
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        let (forward, backward) = (self.directed(x, y), self.directed(y, x));
+        if forward > backward {
+            forward
+        } else {
+            backward
+        }
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Minkowski;
+
+    #[test]
+    fn takes_the_larger_directed_distance() {
+        let hausdorff = Hausdorff::<f64, f64>::new(Box::new(Minkowski::new(1.)));
+        // The points (0, 0) and (1, 0) against (0, 0) and (3, 4). Every point
+        // of x is within 1 of y, but (3, 4) is 6 from its closest point (1, 0).
+        let (x, y) = ([0., 0., 1., 0.], [0., 0., 3., 4.]);
+        assert_eq!(hausdorff.directed(&x, &y), 1.);
+        assert_eq!(hausdorff.directed(&y, &x), 6.);
+        assert_eq!(hausdorff.one_to_one(&x, &y), 6.);
+        assert_eq!(hausdorff.one_to_one(&y, &x), 6.);
+        assert_eq!(hausdorff.one_to_one(&x, &x), 0.);
+    }
+}
@@ -0,0 +1,129 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+use super::band::Band;
+
+/// Longest Common Subsequence distance (Vlachos et al., 2002).
+///
+/// Two points match when they are within `epsilon` of each other and at
+/// most `delta` time steps apart. The distance is `1 - lcss / min(n, m)`
+/// where `lcss` is the number of matched points, so it lies in `[0, 1]` and
+/// ignores outliers that find no match.
+#[derive(Debug)]
+pub struct Lcss<T: Number, U: Number> {
+    epsilon: T,
+    delta: usize,
+    band: Band,
+    _u: PhantomData<U>,
+}
+
+impl<T: Number, U: Number> Lcss<T, U> {
+    pub fn new(epsilon: T, delta: usize) -> Self {
+        Self {
+            epsilon,
+            delta,
+            band: Band::None,
+            _u: PhantomData,
+        }
+    }
+
+    /// LCSS that only fills the cells of the DP within `width` steps of the
+    /// diagonal, as in a Sakoe-Chiba band. This gives the same distance as
+    /// `new` whenever the best matching stays within the band.
+    pub fn with_band(epsilon: T, delta: usize, width: usize) -> Self {
+        Self {
+            band: Band::SakoeChiba { width },
+            ..Self::new(epsilon, delta)
+        }
+    }
+
+    fn is_match(&self, x: T, y: T, i: usize, j: usize) -> bool {
+        let close = (x.as_f64() - y.as_f64()).abs() <= self.epsilon.as_f64();
+        close && i.abs_diff(j) <= self.delta
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Lcss<T, U> {
+    fn name(&self) -> String {
+        "lcss".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        let min_len = x.len().min(y.len());
+        if min_len == 0 {
+            return if x.len() == y.len() {
+                U::zero()
+            } else {
+                U::one()
+            };
+        }
+
+        // `previous[i + 1]` holds the LCSS of `x[..=i]` and `y[..j]`.
+        let mut previous = vec![0_usize; x.len() + 1];
+        let mut current = vec![0_usize; x.len() + 1];
+
+        // Cells outside the band are left at 0, which never wins a `max`.
+        for (j, &b) in y.iter().enumerate() {
+            let (lo, hi) = self.band.row_bounds(j, x.len(), y.len());
+            for (i, &a) in x.iter().enumerate() {
+                current[i + 1] = if i < lo || i > hi {
+                    0
+                } else if self.is_match(a, b, i, j) {
+                    previous[i] + 1
+                } else {
+                    previous[i + 1].max(current[i])
+                };
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        let lcss = previous[x.len()] as f64;
+        U::from(1. - lcss / min_len as f64).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_matches_within_epsilon() {
+        let lcss = Lcss::<i32, f64>::new(0, usize::MAX);
+        let x = [1, 2, 3, 4];
+        assert_eq!(lcss.one_to_one(&x, &x), 0.);
+        // The outlier finds no match and costs only its share of the length.
+        assert_eq!(lcss.one_to_one(&x, &[1, 2, 100, 4]), 0.25);
+        assert_eq!(lcss.one_to_one(&x, &[5, 6, 7, 8]), 1.);
+        assert_eq!(
+            Lcss::<i32, f64>::new(2, usize::MAX).one_to_one(&x, &[3, 4, 5, 6]),
+            0.
+        );
+    }
+
+    #[test]
+    fn matches_at_most_delta_steps_apart() {
+        let (x, y) = ([1, 2, 3, 4], [9, 1, 2, 3]);
+        assert_eq!(Lcss::<i32, f64>::new(0, 0).one_to_one(&x, &y), 1.);
+        assert_eq!(Lcss::<i32, f64>::new(0, 1).one_to_one(&x, &y), 0.25);
+    }
+
+    #[test]
+    fn narrow_band_matches_unbanded_near_the_diagonal() {
+        // `y` is `x` shifted by one step, so the best matching stays within
+        // a step of the diagonal.
+        let x = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let y = (0..32)
+            .map(|i| ((i + 1) as f64 / 4.).sin())
+            .collect::<Vec<_>>();
+        let (banded, unbanded) = (
+            Lcss::<f64, f64>::with_band(0.05, usize::MAX, 3),
+            Lcss::<f64, f64>::new(0.05, usize::MAX),
+        );
+        assert_eq!(banded.one_to_one(&x, &y), unbanded.one_to_one(&x, &y));
+    }
+}
@@ -0,0 +1,100 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+use ndarray::prelude::*;
+
+/// Mahalanobis distance `sqrt((x - y)^T inv(cov) (x - y))` between feature
+/// vectors, for a fixed covariance matrix.
+#[derive(Debug)]
+pub struct Mahalanobis<U: Number> {
+    inv_cov: Array2<f64>,
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> Mahalanobis<U> {
+    /// Inverts the square covariance matrix `cov`, failing if it is
+    /// singular.
+    pub fn new(cov: ArrayView2<f64>) -> Result<Self, String> {
+        Ok(Self {
+            inv_cov: invert(cov)?,
+            _u: PhantomData,
+        })
+    }
+
+    /// The number of features the covariance matrix describes.
+    pub fn dimension(&self) -> usize {
+        self.inv_cov.nrows()
+    }
+}
+
+/// Gauss-Jordan elimination with partial pivoting.
+fn invert(matrix: ArrayView2<f64>) -> Result<Array2<f64>, String> {
+    let n = matrix.nrows();
+    if matrix.ncols() != n {
+        return Err(format!(
+            "Covariance must be square, got shape {:?}",
+            matrix.shape()
+        ));
+    }
+
+    let scale = matrix.iter().fold(0., |max: f64, v| max.max(v.abs()));
+    let mut a = matrix.to_owned();
+    let mut inverse = Array2::eye(n);
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[[i, col]].abs().total_cmp(&a[[j, col]].abs()))
+            .unwrap();
+        if a[[pivot, col]].abs() <= 1e-12 * scale {
+            return Err("Covariance matrix is singular".to_string());
+        }
+        for k in 0..n {
+            a.swap([col, k], [pivot, k]);
+            inverse.swap([col, k], [pivot, k]);
+        }
+
+        let p = a[[col, col]];
+        a.row_mut(col).mapv_inplace(|v| v / p);
+        inverse.row_mut(col).mapv_inplace(|v| v / p);
+        for row in 0..n {
+            if row != col {
+                let factor = a[[row, col]];
+                let a_col = a.row(col).to_owned();
+                let inv_col = inverse.row(col).to_owned();
+                a.row_mut(row).scaled_add(-factor, &a_col);
+                inverse.row_mut(row).scaled_add(-factor, &inv_col);
+            }
+        }
+    }
+    Ok(inverse)
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Mahalanobis<U> {
+    fn name(&self) -> String {
+        "mahalanobis".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(
+            x.len(),
+            self.dimension(),
+            "x has the wrong number of features"
+        );
+        assert_eq!(
+            y.len(),
+            self.dimension(),
+            "y has the wrong number of features"
+        );
+
+        let diff = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| a.as_f64() - b.as_f64())
+            .collect::<Array1<_>>();
+        let squared = diff.dot(&self.inv_cov.dot(&diff));
+        U::from(squared.max(0.).sqrt()).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
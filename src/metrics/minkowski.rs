@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+/// The Lp distance `(sum |x - y|^p)^(1/p)`, or the max norm when `p` is
+/// infinite.
+///
+/// This works on slices of any length, so it can be used as the child
+/// metric of `DynamicTimeWarping` as well as on whole frames.
+#[derive(Debug)]
+pub struct Minkowski<T: Number, U: Number> {
+    p: f64,
+    _t: PhantomData<T>,
+    _u: PhantomData<U>,
+}
+
+impl<T: Number, U: Number> Minkowski<T, U> {
+    pub fn new(p: f64) -> Self {
+        assert!(p >= 1., "p must be at least 1, got {p}");
+        Self {
+            p,
+            _t: PhantomData,
+            _u: PhantomData,
+        }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for Minkowski<T, U> {
+    fn name(&self) -> String {
+        format!("minkowski_{}", self.p)
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len(), y.len(), "slices must have the same length");
+
+        let differences = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (a.as_f64() - b.as_f64()).abs());
+        let distance = if self.p.is_infinite() {
+            differences.fold(0., f64::max)
+        } else {
+            differences
+                .map(|d| d.powf(self.p))
+                .sum::<f64>()
+                .powf(1. / self.p)
+        };
+        U::from(distance).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ndarray::array;
+
+    use super::*;
+    use crate::metrics::DynamicTimeWarping;
+
+    #[test]
+    fn computes_lp_and_max_norms() {
+        let (x, y) = ([0., 0.], [3., 4.]);
+        let distance = |p: f64| Minkowski::<f64, f64>::new(p).one_to_one(&x, &y);
+        assert_eq!(distance(1.), 7.);
+        assert_eq!(distance(2.), 5.);
+        assert_eq!(distance(f64::INFINITY), 4.);
+    }
+
+    #[test]
+    fn works_as_a_dtw_child() {
+        let dtw = |p: f64| DynamicTimeWarping::<f64, f64>::new(Arc::new(Minkowski::new(p)));
+        let (x, y) = ([0., 1., 2.], [0., 1., 1., 2.]);
+        assert_eq!(dtw(3.).one_to_one(&x, &x), 0.);
+        assert_eq!(dtw(1.).one_to_one(&x, &y), 0.);
+
+        // On multivariate sequences the child sees each whole time step.
+        let (a, b) = (array![[0., 0.], [1., 1.]], array![[3., 4.], [1., 1.]]);
+        assert_eq!(dtw(2.).one_to_one_multi(a.view(), b.view()), 5.);
+        assert_eq!(dtw(f64::INFINITY).one_to_one_multi(a.view(), b.view()), 4.);
+    }
+}
@@ -0,0 +1,36 @@
+mod band;
+mod complex;
+mod correlation;
+mod cosine;
+mod dtw;
+mod edr;
+mod erp;
+mod frechet;
+mod hausdorff;
+mod lcss;
+mod mahalanobis;
+mod minkowski;
+mod pairwise;
+mod properties;
+mod registry;
+mod search;
+mod soft_dtw;
+mod wasserstein;
+
+pub use complex::{ComplexEuclidean, ComplexManhattan};
+pub use correlation::CorrelationDistance;
+pub use cosine::CosineDistance;
+pub use dtw::DynamicTimeWarping;
+pub use edr::Edr;
+pub use erp::Erp;
+pub use frechet::Frechet;
+pub use hausdorff::Hausdorff;
+pub use lcss::Lcss;
+pub use mahalanobis::Mahalanobis;
+pub use minkowski::Minkowski;
+pub use pairwise::pairwise_matrix;
+pub use properties::{check_metric_properties, MetricReport};
+pub use registry::metric_from_name;
+pub use search::{lb_keogh, nearest_neighbor, nearest_neighbor_with_stats, SearchStats};
+pub use soft_dtw::SoftDtw;
+pub use wasserstein::{wasserstein_1d, AmplitudeWasserstein};
@@ -0,0 +1,66 @@
+use clam::{Metric, Number};
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+/// The `n x n` matrix of distances between all pairs of `data`, with rows
+/// computed in parallel.
+///
+/// With `symmetric`, only the upper triangle is computed and mirrored, and
+/// the diagonal is left at zero, which halves the work for metrics with
+/// `d(x, y) == d(y, x)`. Otherwise every entry, including the diagonal, is
+/// computed, e.g. for DTW with asymmetric step weights.
+pub fn pairwise_matrix<T, U>(
+    metric: &(dyn Metric<T, U> + Sync),
+    data: &[&[T]],
+    symmetric: bool,
+) -> Array2<U>
+where
+    T: Number + Sync,
+    U: Number + Send,
+{
+    let n = data.len();
+    let rows = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let start = if symmetric { i + 1 } else { 0 };
+            (start..n)
+                .map(|j| metric.one_to_one(data[i], data[j]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut matrix = Array2::zeros((n, n));
+    for (i, row) in rows.into_iter().enumerate() {
+        let start = n - row.len();
+        for (j, distance) in (start..n).zip(row) {
+            matrix[[i, j]] = distance;
+            if symmetric {
+                matrix[[j, i]] = distance;
+            }
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::metrics::{DynamicTimeWarping, Minkowski};
+
+    #[test]
+    fn builds_a_square_matrix_with_a_zero_diagonal() {
+        let frames = [[0., 1., 2.], [1., 1., 1.], [2., 1., 0.], [0., 0., 5.]];
+        let data = frames.iter().map(|f| f.as_slice()).collect::<Vec<_>>();
+        let dtw = DynamicTimeWarping::<f64, f64>::new(Arc::new(Minkowski::new(1.)));
+
+        let mirrored = pairwise_matrix(&dtw, &data, true);
+        let full = pairwise_matrix(&dtw, &data, false);
+        assert_eq!(mirrored.shape(), [4, 4]);
+        assert!(mirrored.diag().iter().all(|&d| d == 0.));
+        assert_eq!(mirrored, mirrored.t());
+        assert_eq!(full, mirrored);
+        assert_eq!(full[[0, 1]], dtw.one_to_one(data[0], data[1]));
+    }
+}
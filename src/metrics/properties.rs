@@ -0,0 +1,91 @@
+use clam::{Metric, Number};
+
+/// Violations of the metric axioms found by `check_metric_properties`.
+///
+/// Samples are referred to by their index in the slice that was checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricReport {
+    /// Samples `i` with `d(i, i) != 0`.
+    pub identity_violations: Vec<usize>,
+    /// Pairs `(i, j)` with `d(i, j) != d(j, i)`.
+    pub symmetry_violations: Vec<(usize, usize)>,
+    /// Triples `(i, j, k)` with `d(i, k) > d(i, j) + d(j, k)`.
+    pub triangle_violations: Vec<(usize, usize, usize)>,
+    /// The number of triples checked for the triangle inequality.
+    pub triangle_checks: usize,
+    /// The largest `d(i, k) - (d(i, j) + d(j, k))` over all violations.
+    pub max_triangle_excess: f64,
+}
+
+impl MetricReport {
+    /// Whether no violations were found among the checked samples.
+    pub fn is_metric(&self) -> bool {
+        self.identity_violations.is_empty()
+            && self.symmetry_violations.is_empty()
+            && self.triangle_violations.is_empty()
+    }
+
+    /// The fraction of checked triples that violated the triangle
+    /// inequality.
+    pub fn triangle_violation_rate(&self) -> f64 {
+        if self.triangle_checks == 0 {
+            0.
+        } else {
+            self.triangle_violations.len() as f64 / self.triangle_checks as f64
+        }
+    }
+}
+
+/// Checks identity, symmetry and the triangle inequality over all pairs and
+/// triples of `samples`, reporting any violations instead of panicking.
+///
+/// DTW and several other distances in this crate are not true metrics, so
+/// this quantifies how badly they violate the axioms on real data. CLAM's
+/// metric trees rely on the triangle inequality for correct search.
+pub fn check_metric_properties<T: Number, U: Number>(
+    metric: &dyn Metric<T, U>,
+    samples: &[&[T]],
+) -> MetricReport {
+    let n = samples.len();
+    let distances = samples
+        .iter()
+        .map(|x| {
+            samples
+                .iter()
+                .map(|y| metric.one_to_one(x, y).as_f64())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let tolerance = |v: f64| 1e-9 * v.abs().max(1.);
+
+    let mut report = MetricReport::default();
+    for i in 0..n {
+        if distances[i][i].abs() > tolerance(0.) {
+            report.identity_violations.push(i);
+        }
+        for j in (i + 1)..n {
+            if (distances[i][j] - distances[j][i]).abs() > tolerance(distances[i][j]) {
+                report.symmetry_violations.push((i, j));
+            }
+        }
+    }
+
+    for (i, from_i) in distances.iter().enumerate() {
+        for j in 0..n {
+            for k in 0..n {
+                if i == j || j == k || i == k {
+                    continue;
+                }
+                report.triangle_checks += 1;
+                let bound = from_i[j] + distances[j][k];
+                let excess = from_i[k] - bound;
+                if excess > tolerance(bound) {
+                    report.triangle_violations.push((i, j, k));
+                    report.max_triangle_excess = report.max_triangle_excess.max(excess);
+                }
+            }
+        }
+    }
+
+    report
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use clam::{Metric, Number};
+
+use super::{
+    ComplexEuclidean, ComplexManhattan, DynamicTimeWarping, Edr, Erp, Frechet, Hausdorff, Lcss,
+    Minkowski, SoftDtw,
+};
+
+/// The matching threshold `metric_from_name` gives LCSS and EDR.
+const EPSILON: f64 = 0.1;
+
+/// The smoothing `metric_from_name` gives soft-DTW.
+const GAMMA: f64 = 1.;
+
+/// Builds one of the crate's metrics from its name, for selecting a metric
+/// from the command line or a config file.
+///
+/// `name` is one of `"dtw"`, `"ddtw"` (derivative DTW), `"soft_dtw"`,
+/// `"lcss"`, `"edr"`, `"erp"`, `"frechet"` or `"hausdorff"`, and `child` is
+/// the metric the elastic measures compare time steps with.
+///
+/// The DTW variants compare one value at a time, so their `child` is one of
+/// `"euclidean"`, `"manhattan"` or `"minkowski_{p}"`. Fréchet and Hausdorff
+/// compare `[i, q]` pairs, so they also take `"complex_euclidean"` and
+/// `"complex_manhattan"`. LCSS and EDR compare points by threshold, and ERP
+/// by absolute difference, so they take no child and `child` must be empty.
+///
+/// LCSS and EDR get an `epsilon` of 0.1, LCSS no time constraint, ERP a gap
+/// of 0 and soft-DTW a `gamma` of 1. Use the constructors directly for other
+/// parameters.
+///
+/// Returns `None` if either name is unknown or `child` does not suit `name`.
+pub fn metric_from_name<T: Number + 'static, U: Number + 'static>(
+    name: &str,
+    child: &str,
+) -> Option<Box<dyn Metric<T, U>>> {
+    let metric: Box<dyn Metric<T, U>> = match name {
+        "dtw" => Box::new(DynamicTimeWarping::new(Arc::from(scalar_child(child)?))),
+        "ddtw" => {
+            let child = Arc::from(scalar_child(child)?);
+            Box::new(DynamicTimeWarping::derivative(child))
+        }
+        "soft_dtw" => Box::new(SoftDtw::new(Arc::from(scalar_child(child)?), GAMMA)),
+        "frechet" => Box::new(Frechet::new(pair_child(child)?)),
+        "hausdorff" => Box::new(Hausdorff::new(pair_child(child)?)),
+        "lcss" | "edr" | "erp" if !child.is_empty() => return None,
+        "lcss" => Box::new(Lcss::<T, U>::new(T::from(EPSILON)?, usize::MAX)),
+        "edr" => Box::new(Edr::<T, U>::new(T::from(EPSILON)?)),
+        "erp" => Box::new(Erp::<T, U>::new(T::zero())),
+        _ => return None,
+    };
+    Some(metric)
+}
+
+/// A child metric for single values, i.e. a Minkowski distance.
+fn scalar_child<T: Number + 'static, U: Number + 'static>(
+    name: &str,
+) -> Option<Box<dyn Metric<T, U> + Send + Sync>> {
+    let p = match name {
+        "euclidean" => 2.,
+        "manhattan" => 1.,
+        _ => name
+            .strip_prefix("minkowski_")?
+            .parse::<f64>()
+            .ok()
+            .filter(|&p| p >= 1.)?,
+    };
+    Some(Box::new(Minkowski::new(p)))
+}
+
+/// A child metric for `[i, q]` pairs.
+fn pair_child<T: Number + 'static, U: Number + 'static>(
+    name: &str,
+) -> Option<Box<dyn Metric<T, U> + Send + Sync>> {
+    match name {
+        "complex_euclidean" => Some(Box::new(ComplexEuclidean::new())),
+        "complex_manhattan" => Some(Box::new(ComplexManhattan::new())),
+        _ => scalar_child(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_children_that_do_not_fit() {
+        for child in [
+            "complex_euclidean",
+            "cosine",
+            "correlation",
+            "minkowski_0.5",
+        ] {
+            assert!(metric_from_name::<f64, f64>("dtw", child).is_none());
+        }
+        assert!(metric_from_name::<f64, f64>("frechet", "cosine").is_none());
+        assert!(metric_from_name::<f64, f64>("lcss", "euclidean").is_none());
+        assert!(metric_from_name::<f64, f64>("warp", "euclidean").is_none());
+    }
+
+    #[test]
+    fn built_metrics_run() {
+        let (x, y) = ([0., 1., 2., 3.], [0., 1., 2., 4.]);
+        for (name, child) in [
+            ("dtw", "euclidean"),
+            ("ddtw", "manhattan"),
+            ("soft_dtw", "minkowski_3"),
+            ("frechet", "complex_euclidean"),
+            ("hausdorff", "complex_manhattan"),
+            ("lcss", ""),
+            ("edr", ""),
+            ("erp", ""),
+        ] {
+            let metric = metric_from_name::<f64, f64>(name, child).unwrap();
+            assert!(
+                metric.one_to_one(&x, &x) <= metric.one_to_one(&x, &y),
+                "{name}"
+            );
+        }
+    }
+}
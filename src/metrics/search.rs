@@ -0,0 +1,143 @@
+use clam::Number;
+
+use super::DynamicTimeWarping;
+
+/// The LB_Keogh lower bound (Keogh, 2002) on the DTW distance between
+/// equal-length `query` and `candidate` within a Sakoe-Chiba band of
+/// `width`, with an absolute difference point cost.
+///
+/// This sums how far each point of `candidate` lies outside the envelope
+/// of `query`, i.e. the running max and min of `query` over `width` steps
+/// on either side.
+pub fn lb_keogh(query: &[f64], candidate: &[f64], width: usize) -> f64 {
+    assert_eq!(
+        query.len(),
+        candidate.len(),
+        "LB_Keogh needs sequences of the same length"
+    );
+    let n = query.len();
+    candidate
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let window = &query[i.saturating_sub(width)..(i + width + 1).min(n)];
+            let upper = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let lower = window.iter().copied().fold(f64::INFINITY, f64::min);
+            if c > upper {
+                c - upper
+            } else if c < lower {
+                lower - c
+            } else {
+                0.
+            }
+        })
+        .sum()
+}
+
+/// How much work `nearest_neighbor_with_stats` did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Candidates skipped because their lower bound was no better than the
+    /// best distance so far.
+    pub pruned: usize,
+    /// Candidates whose DTW was computed, whether or not it finished.
+    pub dtw_computations: usize,
+    /// DTW computations that stopped early at the best distance so far.
+    pub abandoned: usize,
+}
+
+/// The index and DTW distance of the candidate closest to `query`, with DTW
+/// restricted to a Sakoe-Chiba band of `width`.
+///
+/// Candidates are visited in order of their `lb_keogh` bound, so those that
+/// cannot beat the best distance so far are skipped, and the rest abandon
+/// their DTW once it exceeds that distance. This gives the same answer as
+/// brute force only when the lower bound holds, i.e. when `dtw` uses an
+/// absolute difference child metric (e.g. `Minkowski` on single points) and
+/// step weights of at least 1. Any preprocessing `dtw` does is applied
+/// before bounding.
+///
+/// Panics if there are no candidates or their lengths differ from the
+/// query's.
+pub fn nearest_neighbor<U: Number>(
+    dtw: &DynamicTimeWarping<f64, U>,
+    query: &[f64],
+    candidates: &[&[f64]],
+    width: usize,
+) -> (usize, U) {
+    nearest_neighbor_with_stats(dtw, query, candidates, width).0
+}
+
+/// Like `nearest_neighbor`, but also reporting how many candidates were
+/// pruned by the lower bound.
+pub fn nearest_neighbor_with_stats<U: Number>(
+    dtw: &DynamicTimeWarping<f64, U>,
+    query: &[f64],
+    candidates: &[&[f64]],
+    width: usize,
+) -> ((usize, U), SearchStats) {
+    assert!(!candidates.is_empty(), "Need at least one candidate");
+
+    let query = dtw.prepare(query);
+    let candidates = candidates
+        .iter()
+        .map(|c| dtw.prepare(c))
+        .collect::<Vec<_>>();
+    let mut bounds = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, lb_keogh(&query, c, width)))
+        .collect::<Vec<_>>();
+    bounds.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    let mut stats = SearchStats::default();
+    let mut best = (bounds[0].0, U::max_value());
+    for (k, &(i, bound)) in bounds.iter().enumerate() {
+        if k > 0 && bound >= best.1.as_f64() {
+            stats.pruned = bounds.len() - k;
+            break;
+        }
+        stats.dtw_computations += 1;
+        match dtw.banded_with_cutoff(&query, &candidates[i], width, best.1) {
+            Some(distance) if distance < best.1 || k == 0 => best = (i, distance),
+            Some(_) => (),
+            None => stats.abandoned += 1,
+        }
+    }
+    (best, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use clam::Metric;
+
+    use super::*;
+    use crate::metrics::Minkowski;
+
+    #[test]
+    fn pruning_keeps_the_exact_nearest_neighbor() {
+        let width = 2;
+        let dtw =
+            DynamicTimeWarping::<f64, f64>::with_sakoe_chiba(Arc::new(Minkowski::new(1.)), width);
+        let query = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let candidates = [5., 0.3, 8., -6., 0.1, 12.]
+            .iter()
+            .map(|offset| query.iter().map(|q| q + offset).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let candidates = candidates.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let brute_force = candidates
+            .iter()
+            .map(|c| dtw.one_to_one(&query, c))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        let (nearest, stats) = nearest_neighbor_with_stats(&dtw, &query, &candidates, width);
+
+        assert_eq!(nearest, brute_force);
+        assert!(stats.pruned > 0);
+        assert_eq!(stats.dtw_computations + stats.pruned, candidates.len());
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use clam::{Metric, Number};
+
+/// Soft-DTW (Cuturi & Blondel, 2017), a differentiable relaxation of DTW.
+///
+/// The hard `min` over the three neighboring cells is replaced by the
+/// soft-min `-gamma * logsumexp(-neighbors / gamma)`. As `gamma` approaches
+/// 0 this approaches ordinary DTW.
+///
+/// The soft-min of several values is smaller than their minimum, so with a
+/// large `gamma` the output can be negative. This is expected for soft-DTW,
+/// but it means `U` should be a signed type.
+///
+/// As with `DynamicTimeWarping`, the child is held in an `Arc` so that one
+/// instance can be shared across rayon threads.
+#[derive(Debug)]
+pub struct SoftDtw<T: Number, U: Number> {
+    child_metric: Arc<dyn Metric<T, U> + Send + Sync>,
+    gamma: f64,
+}
+
+impl<T: Number, U: Number> SoftDtw<T, U> {
+    pub fn new(child_metric: Arc<dyn Metric<T, U> + Send + Sync>, gamma: f64) -> Self {
+        assert!(gamma > 0., "gamma must be positive, got {gamma}");
+        Self {
+            child_metric,
+            gamma,
+        }
+    }
+
+    fn soft_min(&self, values: [f64; 3]) -> f64 {
+        let scaled = values.map(|v| -v / self.gamma);
+        let max = scaled.into_iter().fold(f64::NEG_INFINITY, f64::max);
+        if max == f64::NEG_INFINITY {
+            return f64::INFINITY;
+        }
+        let sum = scaled.into_iter().map(|v| (v - max).exp()).sum::<f64>();
+        -self.gamma * (max + sum.ln())
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for SoftDtw<T, U> {
+    fn name(&self) -> String {
+        format!("soft_dtw_{}", self.child_metric.name())
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        if x.is_empty() || y.is_empty() {
+            return if x.len() == y.len() {
+                U::zero()
+            } else {
+                U::max_value()
+            };
+        }
+
+        let mut previous = vec![f64::INFINITY; x.len()];
+        let mut current = vec![f64::INFINITY; x.len()];
+
+        for j in 0..y.len() {
+            for i in 0..x.len() {
+                let neighbor = match (i, j) {
+                    (0, 0) => 0.,
+                    (0, _) => previous[0],
+                    (_, 0) => current[i - 1],
+                    _ => self.soft_min([previous[i - 1], previous[i], current[i - 1]]),
+                };
+                let distance: U = self.child_metric.one_to_one(&x[i..=i], &y[j..=j]);
+                current[i] = distance.as_f64() + neighbor;
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        U::from(previous[x.len() - 1]).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use super::*;
+    use crate::metrics::{DynamicTimeWarping, Minkowski};
+
+    #[test]
+    fn shares_one_instance_across_threads() {
+        let soft_dtw = SoftDtw::<f64, f64>::new(Arc::new(Minkowski::new(2.)), 0.1);
+        let frames = (0..16)
+            .map(|k| {
+                (0..32)
+                    .map(|i| ((i + k) as f64 / 4.).sin())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let sequential = frames
+            .iter()
+            .map(|f| soft_dtw.one_to_one(&frames[0], f))
+            .collect::<Vec<_>>();
+        let parallel = frames
+            .par_iter()
+            .map(|f| soft_dtw.one_to_one(&frames[0], f))
+            .collect::<Vec<_>>();
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn small_gamma_approaches_dtw() {
+        let x = (0..32).map(|i| (i as f64 / 4.).sin()).collect::<Vec<_>>();
+        let y = (0..24).map(|i| (i as f64 / 3.).cos()).collect::<Vec<_>>();
+        let dtw = DynamicTimeWarping::<f64, f64>::new(Arc::new(Minkowski::new(2.)));
+        let soft_dtw = SoftDtw::<f64, f64>::new(Arc::new(Minkowski::new(2.)), 1e-4);
+        let (hard, soft) = (dtw.one_to_one(&x, &y), soft_dtw.one_to_one(&x, &y));
+        assert!((hard - soft).abs() < 1e-2, "{hard} vs {soft}");
+    }
+
+    #[test]
+    fn large_gamma_can_be_negative() {
+        let soft_dtw = SoftDtw::<f64, f64>::new(Arc::new(Minkowski::new(2.)), 10.);
+        assert!(soft_dtw.one_to_one(&[0.; 8], &[0.; 8]) < 0.);
+    }
+}
@@ -0,0 +1,128 @@
+use std::marker::PhantomData;
+
+use clam::{Metric, Number};
+
+/// The 1-Wasserstein (earth mover's) distance between the empirical
+/// distributions of `a` and `b`, i.e. the integral of `|F_a(x) - F_b(x)|`
+/// over `x` for their CDFs `F_a` and `F_b`.
+///
+/// The samples need not be sorted or of equal number. Panics if either is
+/// empty.
+pub fn wasserstein_1d(a: &[f64], b: &[f64]) -> f64 {
+    assert!(
+        !a.is_empty() && !b.is_empty(),
+        "distributions must have at least one sample"
+    );
+    let sorted = |x: &[f64]| {
+        let mut x = x.to_vec();
+        x.sort_by(f64::total_cmp);
+        x
+    };
+    let (a, b) = (sorted(a), sorted(b));
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+
+    // Step through the merged samples, adding the area between the CDFs
+    // since the previous sample.
+    let (mut i, mut j) = (0, 0);
+    let mut previous = a[0].min(b[0]);
+    let mut distance = 0.;
+    while i < a.len() || j < b.len() {
+        let next = match (a.get(i), b.get(j)) {
+            (Some(&x), Some(&y)) => x.min(y),
+            (Some(&x), None) => x,
+            (None, Some(&y)) => y,
+            (None, None) => unreachable!(),
+        };
+        distance += (i as f64 / na - j as f64 / nb).abs() * (next - previous);
+        previous = next;
+        while i < a.len() && a[i] <= next {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= next {
+            j += 1;
+        }
+    }
+    distance
+}
+
+/// `wasserstein_1d` between the amplitude distributions of two frames of
+/// interleaved IQ samples, i.e. `[i_0, q_0, i_1, q_1, ...]`.
+///
+/// This ignores the order of the samples entirely, so it compares frames by
+/// their envelope statistics alone.
+#[derive(Debug, Default)]
+pub struct AmplitudeWasserstein<U: Number> {
+    _u: PhantomData<U>,
+}
+
+impl<U: Number> AmplitudeWasserstein<U> {
+    pub fn new() -> Self {
+        Self { _u: PhantomData }
+    }
+}
+
+impl<T: Number, U: Number> Metric<T, U> for AmplitudeWasserstein<U> {
+    fn name(&self) -> String {
+        "amplitude_wasserstein".to_string()
+    }
+
+    fn one_to_one(&self, x: &[T], y: &[T]) -> U {
+        assert_eq!(x.len() % 2, 0, "frames must hold interleaved IQ pairs");
+        assert_eq!(y.len() % 2, 0, "frames must hold interleaved IQ pairs");
+
+        let amplitudes = |frame: &[T]| {
+            frame
+                .chunks_exact(2)
+                .map(|iq| iq[0].as_f64().hypot(iq[1].as_f64()))
+                .collect::<Vec<_>>()
+        };
+        U::from(wasserstein_1d(&amplitudes(x), &amplitudes(y))).unwrap()
+    }
+
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` evenly spaced samples of the uniform distribution on
+    /// `[start, start + width]`.
+    fn uniform(n: usize, start: f64, width: f64) -> Vec<f64> {
+        (0..n)
+            .map(|k| start + width * (k as f64 + 0.5) / n as f64)
+            .collect()
+    }
+
+    #[test]
+    fn matches_the_closed_form_for_uniform_distributions() {
+        // Shifting a distribution by `s` moves all of its mass `s`.
+        let distance = wasserstein_1d(&uniform(1000, 0., 1.), &uniform(1000, 0.3, 1.));
+        assert!((distance - 0.3).abs() < 1e-9, "{distance}");
+        // The sample counts may differ.
+        let distance = wasserstein_1d(&uniform(1000, 0., 1.), &uniform(400, 0.3, 1.));
+        assert!((distance - 0.3).abs() < 1e-2, "{distance}");
+        // For U(0, 1) and U(0, 2) the CDFs enclose an area of 1/2.
+        let distance = wasserstein_1d(&uniform(1000, 0., 1.), &uniform(1000, 0., 2.));
+        assert!((distance - 0.5).abs() < 1e-2, "{distance}");
+    }
+
+    #[test]
+    fn compares_frames_by_amplitude() {
+        let frame = |amplitudes: Vec<f64>| {
+            amplitudes
+                .into_iter()
+                .enumerate()
+                .flat_map(|(t, r)| {
+                    let angle = t as f64 / 3.;
+                    [r * angle.cos(), r * angle.sin()]
+                })
+                .collect::<Vec<_>>()
+        };
+        let (x, y) = (frame(uniform(512, 1., 1.)), frame(uniform(512, 1.5, 1.)));
+        let distance: f64 = AmplitudeWasserstein::new().one_to_one(&x, &y);
+        assert!((distance - 0.5).abs() < 1e-9, "{distance}");
+    }
+}
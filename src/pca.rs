@@ -0,0 +1,80 @@
+//! Principal component analysis of flattened frames, e.g. from
+//! `RadioData::join_flat`.
+//!
+//! Behind the `pca` feature, since the SVD needs `ndarray-linalg`, which
+//! builds OpenBLAS from source.
+
+use ndarray::prelude::*;
+use ndarray_linalg::{JobSvd, SVDDC};
+
+/// The mean and leading principal axes of a set of rows, from `fit_pca`.
+#[derive(Debug, Clone)]
+pub struct PcaModel {
+    mean: Array1<f64>,
+    components: Array2<f64>,
+    explained_variance: Array1<f64>,
+}
+
+/// Fits PCA to the rows of `data`, keeping the top `n_components` axes.
+///
+/// The rows are centered on their mean and the axes are the right singular
+/// vectors of the centered data, in order of decreasing variance.
+///
+/// Panics if `n_components` exceeds `min(n_rows, n_cols)`, or if the SVD
+/// fails to converge.
+pub fn fit_pca(data: ArrayView2<f64>, n_components: usize) -> PcaModel {
+    let (n, d) = data.dim();
+    assert!(
+        n_components <= n.min(d),
+        "Cannot keep {} components of {} rows of dimension {}",
+        n_components,
+        n,
+        d
+    );
+
+    let mean = data.mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(d));
+    let centered = &data - &mean;
+    let (_, singular_values, vt) = centered
+        .svddc(JobSvd::Some)
+        .expect("SVD of the centered data did not converge");
+    let vt = vt.unwrap();
+
+    let denominator = n.saturating_sub(1).max(1) as f64;
+    PcaModel {
+        mean,
+        components: vt.slice(s![..n_components, ..]).to_owned(),
+        explained_variance: singular_values
+            .slice(s![..n_components])
+            .mapv(|s| s * s / denominator),
+    }
+}
+
+impl PcaModel {
+    /// Projects the rows of `data` onto the principal axes, centering them
+    /// on the mean of the data the model was fit to.
+    pub fn transform(&self, data: ArrayView2<f64>) -> Array2<f64> {
+        assert_eq!(
+            data.ncols(),
+            self.mean.len(),
+            "Data has dimension {} but the model was fit to dimension {}",
+            data.ncols(),
+            self.mean.len()
+        );
+        (&data - &self.mean).dot(&self.components.t())
+    }
+
+    /// The mean of the data the model was fit to.
+    pub fn mean(&self) -> ArrayView1<f64> {
+        self.mean.view()
+    }
+
+    /// The principal axes, one per row.
+    pub fn components(&self) -> ArrayView2<f64> {
+        self.components.view()
+    }
+
+    /// The (sample) variance of the fit data along each principal axis.
+    pub fn explained_variance(&self) -> ArrayView1<f64> {
+        self.explained_variance.view()
+    }
+}
@@ -0,0 +1,99 @@
+//! Checks for corrupt frames, such as all-zero or hard-clipped exports.
+
+use ndarray::prelude::*;
+
+/// The fraction of clipped samples above which `FrameQuality::is_suspicious`
+/// flags a frame. Noisy frames rarely repeat their exact extremes, so even a
+/// few samples pinned there suggest an ADC limit.
+pub const CLIPPED_THRESHOLD: f64 = 0.01;
+
+/// Data-hygiene statistics of one frame, from `frame_quality`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameQuality {
+    /// Whether every sample of both channels is zero.
+    pub all_zero: bool,
+    /// The fraction of values, over both channels, that repeat their
+    /// channel's minimum or maximum, i.e. every value at an extreme but the
+    /// first one there.
+    pub clipped: f64,
+    /// The peak-to-peak range, the largest value minus the smallest, over
+    /// both channels.
+    pub dynamic_range: f64,
+}
+
+impl FrameQuality {
+    /// Whether the frame is all zeros or has more than `CLIPPED_THRESHOLD` of
+    /// its values clipped.
+    pub fn is_suspicious(&self) -> bool {
+        self.all_zero || self.clipped > CLIPPED_THRESHOLD
+    }
+}
+
+/// The `FrameQuality` of a `(n, 2)` frame.
+///
+/// A value only counts as clipped when it exactly equals its channel's
+/// extreme, as when samples saturate at an ADC limit, and the first value at
+/// each extreme does not count, so a clean frame of any length has a
+/// `clipped` fraction of 0. Constant channels, including all-zero ones,
+/// count as clipped but for one value.
+pub fn frame_quality(frame: ArrayView2<f64>) -> FrameQuality {
+    let all_zero = frame.iter().all(|&v| v == 0.);
+
+    let mut at_extremes = 0_usize;
+    let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+    for channel in frame.columns() {
+        let min = channel.fold(f64::INFINITY, |m, &v| m.min(v));
+        let max = channel.fold(f64::NEG_INFINITY, |m, &v| m.max(v));
+        let at_min = channel.iter().filter(|&&v| v == min).count();
+        let at_max = channel.iter().filter(|&&v| v == max).count();
+        at_extremes += if min == max {
+            // A constant channel has every value at both extremes.
+            at_min.saturating_sub(1)
+        } else {
+            at_min.saturating_sub(1) + at_max.saturating_sub(1)
+        };
+        range = (range.0.min(min), range.1.max(max));
+    }
+
+    let clipped = if frame.is_empty() {
+        0.
+    } else {
+        at_extremes as f64 / frame.len() as f64
+    };
+    let dynamic_range = if frame.is_empty() {
+        0.
+    } else {
+        range.1 - range.0
+    };
+    FrameQuality {
+        all_zero,
+        clipped,
+        dynamic_range,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_short_frames_are_not_suspicious() {
+        let frame = Array2::from_shape_fn((128, 2), |(t, c)| (t as f64 * 0.3 + c as f64).sin());
+        let quality = frame_quality(frame.view());
+        assert_eq!(quality.clipped, 0.);
+        assert!(!quality.is_suspicious());
+    }
+
+    #[test]
+    fn flags_clipped_and_all_zero_frames() {
+        let clipped = Array2::from_shape_fn((128, 2), |(t, c)| {
+            (t as f64 * 0.3 + c as f64).sin().clamp(-0.8, 0.8)
+        });
+        assert!(frame_quality(clipped.view()).is_suspicious());
+
+        let zeros = Array2::<f64>::zeros((128, 2));
+        let quality = frame_quality(zeros.view());
+        assert!(quality.all_zero && quality.is_suspicious());
+        assert_eq!(quality.dynamic_range, 0.);
+    }
+}
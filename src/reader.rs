@@ -1,62 +1,1276 @@
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use ndarray::prelude::*;
+use num_complex::Complex;
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
 
-fn open_hdf5_file(folders_from_root: &[&str], data_name: &str) -> hdf5::Result<hdf5::File> {
-    let mut data_dir = std::path::PathBuf::from("/");
+use crate::metrics::{ComplexEuclidean, DynamicTimeWarping};
+use crate::quality::{self, FrameQuality};
 
-    for &folder in folders_from_root {
-        data_dir.push(folder)
+/// The seed used to sample frames unless another is given.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// The SNR label given to the single level of the noise file, unless
+/// overridden with `RadioFile::with_noise_snr_db`.
+pub const NOISE_SNR_DB: i32 = -50;
+
+/// The environment variable `RadioData::read_from_env` takes the data
+/// directory from.
+pub const DATA_DIR_VAR: &str = "RADIOML_DATA_DIR";
+
+/// The data directory `RadioData::read_from_env` falls back to, relative to
+/// the working directory, when `DATA_DIR_VAR` is unset.
+pub const DEFAULT_DATA_DIR: &str = "data";
+
+/// An SNR range that includes every level, noise included.
+const ALL_SNRS: RangeInclusive<i32> = i32::MIN..=i32::MAX;
+
+/// Errors from reading and sampling a `RadioFile`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReaderError {
+    /// More frames were requested from an SNR level than it holds.
+    TooManySamples { requested: usize, available: usize },
+    /// Any other failure, such as a missing dataset or an unexpected shape.
+    Other(String),
+}
+
+impl std::fmt::Display for ReaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManySamples {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Cannot sample {} frames from an SNR level of {} frames",
+                requested, available
+            ),
+            Self::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ReaderError {}
+
+impl From<String> for ReaderError {
+    fn from(reason: String) -> Self {
+        Self::Other(reason)
+    }
+}
+
+impl From<ReaderError> for String {
+    fn from(error: ReaderError) -> Self {
+        error.to_string()
+    }
+}
+
+/// A mismatch found by `validate_sampled` between what was read and what
+/// the sampling should have produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Frames of `modulation` at level `snr`, where known, had `actual`
+    /// shape instead of `expected`.
+    Shape {
+        modulation: Option<ModulationMode>,
+        snr: Option<i32>,
+        expected: Vec<usize>,
+        actual: Vec<usize>,
+    },
+    /// A file of `modulation` had `actual` SNR levels instead of `expected`.
+    LevelCount {
+        modulation: ModulationMode,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl ValidationError {
+    /// Attributes a `Shape` error to `modulation`, for errors from a level.
+    fn for_mode(self, mode: ModulationMode) -> Self {
+        match self {
+            Self::Shape {
+                snr,
+                expected,
+                actual,
+                ..
+            } => Self::Shape {
+                modulation: Some(mode),
+                snr,
+                expected,
+                actual,
+            },
+            error => error,
+        }
     }
+}
 
-    let path = {
-        let mut path = data_dir.clone();
-        path.push(data_name);
-        assert!(path.exists(), "{:?} does not exist.", &path);
-        path
-    };
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Shape {
+                modulation,
+                snr,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Expected frames of shape {:?} but got {:?}",
+                    expected, actual
+                )?;
+                if let Some(modulation) = modulation {
+                    write!(f, " for {:?}", modulation)?;
+                }
+                if let Some(snr) = snr {
+                    write!(f, " at {}dB", snr)?;
+                }
+                Ok(())
+            }
+            Self::LevelCount {
+                modulation,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Expected {} SNR levels but got {} for {:?}",
+                expected, actual, modulation
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for String {
+    fn from(error: ValidationError) -> Self {
+        error.to_string()
+    }
+}
+
+fn open_hdf5_file(folders_from_root: &[&str], data_name: &str) -> hdf5::Result<hdf5::File> {
+    let path = data_path(folders_from_root, data_name);
+    assert!(path.exists(), "{:?} does not exist.", &path);
 
     hdf5::File::open(path)
 }
 
+/// Like `open_hdf5_file`, but a missing file is an error instead of a panic.
+fn try_open_hdf5_file(
+    folders_from_root: &[&str],
+    data_name: &str,
+) -> Result<hdf5::File, ReaderError> {
+    let path = data_path(folders_from_root, data_name);
+    if !path.exists() {
+        return Err(ReaderError::Other(format!("{:?} does not exist.", path)));
+    }
+    hdf5::File::open(&path).map_err(|reason| {
+        ReaderError::Other(format!("Could not open {:?} because {}", path, reason))
+    })
+}
+
+fn data_path(folders_from_root: &[&str], data_name: &str) -> PathBuf {
+    let mut path = PathBuf::from("/");
+    for &folder in folders_from_root {
+        path.push(folder)
+    }
+    path.push(data_name);
+    path
+}
+
 #[derive(Debug)]
 pub struct RadioData {
+    modes: Vec<ModulationMode>,
     samples: Vec<Array3<f64>>,
+    source_rows: Vec<Vec<usize>>,
+    snrs: Vec<Vec<i32>>,
     num_samples: usize,
+    seed: u64,
+    per_mode_seed: bool,
+    dedup_classes: bool,
+}
+
+/// The mode, samples, source rows and per-frame SNRs read from one file.
+type FilePart = (ModulationMode, Array3<f64>, Vec<usize>, Vec<i32>);
+
+/// How a `RadioData` was sampled, without the samples themselves, so that
+/// it can be logged with an experiment and the read reproduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadioDataMeta {
+    pub modes: Vec<ModulationMode>,
+    pub num_samples: usize,
+    pub seed: u64,
+    pub per_mode_seed: bool,
+    /// Whether `OOK_2` frames are labeled as `OOK`.
+    pub dedup_classes: bool,
+    /// The rows of each mode's `X` dataset that were sampled, in the order
+    /// of `modes`.
+    pub source_rows: Vec<Vec<usize>>,
 }
 
 impl RadioData {
     pub fn read(folders_from_root: &[&str], num_samples: usize) -> Self {
-        let samples = ModulationMode::variants()
+        Self::read_with_progress(folders_from_root, num_samples, |done, total, m| {
+            println!("Read from file {:?} ({}/{})", m.data_name(), done, total)
+        })
+    }
+
+    /// Like `read`, but instead of printing, calls `callback` with
+    /// `(files_done, total_files, mode)` on the calling thread as each file
+    /// finishes. Files are still read in parallel.
+    pub fn read_with_progress(
+        folders_from_root: &[&str],
+        num_samples: usize,
+        callback: impl FnMut(usize, usize, ModulationMode),
+    ) -> Self {
+        Self::read_files(
+            folders_from_root,
+            num_samples,
+            DEFAULT_SEED,
+            false,
+            callback,
+        )
+    }
+
+    /// Like `read`, but sampling with the given `seed`.
+    ///
+    /// With `per_mode_seed`, each modulation mode samples with
+    /// `seed + mode.index()`, wrapping past `u64::MAX`, so that different
+    /// classes draw different frame indices. Otherwise every mode uses
+    /// `seed`, as `read` does.
+    pub fn read_seeded(
+        folders_from_root: &[&str],
+        num_samples: usize,
+        seed: u64,
+        per_mode_seed: bool,
+    ) -> Self {
+        Self::read_files(
+            folders_from_root,
+            num_samples,
+            seed,
+            per_mode_seed,
+            |done, total, m| println!("Read from file {:?} ({}/{})", m.data_name(), done, total),
+        )
+    }
+
+    fn read_files(
+        folders_from_root: &[&str],
+        num_samples: usize,
+        seed: u64,
+        per_mode_seed: bool,
+        callback: impl FnMut(usize, usize, ModulationMode),
+    ) -> Self {
+        let handles = ModulationMode::variants()
+            .into_iter()
+            .map(|m| (m, open_hdf5_file(folders_from_root, m.data_name()).unwrap()))
+            .collect();
+        Self::sample_handles(
+            handles,
+            num_samples,
+            seed,
+            per_mode_seed,
+            ALL_SNRS,
+            callback,
+        )
+        .unwrap()
+    }
+
+    /// Like `read`, but skipping modes whose file is missing or cannot be
+    /// read, instead of panicking. Returns the modes that were read along
+    /// with the error for each mode that was skipped. Labels still refer to
+    /// `ModulationMode::index`, so they stay correct with modes missing.
+    pub fn read_available(
+        folders_from_root: &[&str],
+        num_samples: usize,
+    ) -> (Self, Vec<(ModulationMode, ReaderError)>) {
+        let results = ModulationMode::variants()
+            .to_vec()
             .into_par_iter()
-            .inspect(|m| println!("Reading from file {:?}", m.data_name()))
             .map(|m| {
-                let handle = open_hdf5_file(folders_from_root, m.data_name()).unwrap();
-                (m, handle)
+                let file = try_open_hdf5_file(folders_from_root, m.data_name())
+                    .and_then(|handle| RadioFile::new(handle, m, num_samples));
+                (m, file)
             })
-            .map(|(m, handle)| RadioFile::new(handle, m, num_samples).unwrap().join().0)
             .collect::<Vec<_>>();
+
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        for (m, file) in results {
+            match file {
+                Ok(file) => files.push(file.into_part()),
+                Err(error) => errors.push((m, error)),
+            }
+        }
+        (
+            Self::from_parts(files, num_samples, DEFAULT_SEED, false),
+            errors,
+        )
+    }
+
+    /// Like `read`, but from the directory named by the `RADIOML_DATA_DIR`
+    /// environment variable, or `DEFAULT_DATA_DIR` if it is unset, so that
+    /// the path need not be written into code.
+    pub fn read_from_env(num_samples: usize) -> Result<Self, String> {
+        let data_dir = match std::env::var_os(DATA_DIR_VAR) {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let dir = PathBuf::from(DEFAULT_DATA_DIR);
+                if !dir.is_dir() {
+                    return Err(format!(
+                        "{} is not set and the default data directory {:?} does not exist",
+                        DATA_DIR_VAR, dir
+                    ));
+                }
+                dir
+            }
+        };
+        if !data_dir.is_dir() {
+            return Err(format!(
+                "{} is set to {:?}, which is not a directory",
+                DATA_DIR_VAR, data_dir
+            ));
+        }
+        RadioReader::new(data_dir).num_samples(num_samples).build()
+    }
+
+    /// Like `read_seeded`, but sampling once for each of `seeds`, giving one
+    /// `RadioData` per seed in the same order. Each file is read from disk
+    /// once and then sampled for every seed.
+    pub fn read_ensemble(
+        folders_from_root: &[&str],
+        num_samples: usize,
+        seeds: &[u64],
+    ) -> Result<Vec<Self>, String> {
+        let per_mode = ModulationMode::variants()
+            .to_vec()
+            .into_par_iter()
+            .map(|m| {
+                let handle = try_open_hdf5_file(folders_from_root, m.data_name())?;
+                let all_iq = RadioFile::read_frames(&handle)?;
+                seeds
+                    .iter()
+                    .map(|&seed| {
+                        let file = RadioFile::sample_frames(all_iq.view(), m, seed, |snrs| {
+                            Ok(vec![num_samples; snrs.len()])
+                        })?;
+                        Ok(file.into_part())
+                    })
+                    .collect::<Result<Vec<_>, ReaderError>>()
+            })
+            .collect::<Result<Vec<_>, ReaderError>>()?;
+
+        let mut ensemble = seeds.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        for files in per_mode {
+            for (members, file) in ensemble.iter_mut().zip(files) {
+                members.push(file);
+            }
+        }
+        Ok(ensemble
+            .into_iter()
+            .zip(seeds.iter())
+            .map(|(files, &seed)| Self::from_parts(files, num_samples, seed, false))
+            .collect())
+    }
+
+    /// Samples from files the caller has already opened, rather than finding
+    /// them from a base directory as `read` does. Modes keep the order of
+    /// `handles`, and modes not in `handles` are simply absent.
+    pub fn from_handles(
+        handles: Vec<(ModulationMode, hdf5::File)>,
+        num_samples: usize,
+    ) -> Result<Self, String> {
+        Self::sample_handles(
+            handles,
+            num_samples,
+            DEFAULT_SEED,
+            false,
+            ALL_SNRS,
+            |_, _, _| (),
+        )
+    }
+
+    /// Like `from_handles`, but sampling as `read_seeded` does.
+    pub fn from_handles_seeded(
+        handles: Vec<(ModulationMode, hdf5::File)>,
+        num_samples: usize,
+        seed: u64,
+        per_mode_seed: bool,
+    ) -> Result<Self, String> {
+        Self::sample_handles(
+            handles,
+            num_samples,
+            seed,
+            per_mode_seed,
+            ALL_SNRS,
+            |_, _, _| (),
+        )
+    }
+
+    fn sample_handles(
+        handles: Vec<(ModulationMode, hdf5::File)>,
+        num_samples: usize,
+        seed: u64,
+        per_mode_seed: bool,
+        snrs: RangeInclusive<i32>,
+        mut callback: impl FnMut(usize, usize, ModulationMode),
+    ) -> Result<Self, String> {
+        let total = handles.len();
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let files = std::thread::scope(|scope| {
+            let worker = scope.spawn(move || {
+                handles
+                    .into_par_iter()
+                    .map_with(sender, |sender, (m, handle)| {
+                        let seed = if per_mode_seed {
+                            seed.wrapping_add(m.index() as u64)
+                        } else {
+                            seed
+                        };
+                        let file =
+                            RadioFile::new_in_snr_range(handle, m, num_samples, seed, &snrs)?;
+                        let part = file.into_part();
+                        sender.send(part.0).unwrap();
+                        Ok::<_, String>(part)
+                    })
+                    .collect::<Result<Vec<_>, String>>()
+            });
+            for (done, m) in receiver.iter().enumerate() {
+                callback(done + 1, total, m);
+            }
+            worker.join().unwrap()
+        })?;
+
+        Ok(Self::from_parts(files, num_samples, seed, per_mode_seed))
+    }
+
+    /// Assembles the `RadioFile::into_part` of each file.
+    fn from_parts(
+        files: Vec<FilePart>,
+        num_samples: usize,
+        seed: u64,
+        per_mode_seed: bool,
+    ) -> Self {
+        let mut modes = Vec::with_capacity(files.len());
+        let mut samples = Vec::with_capacity(files.len());
+        let mut source_rows = Vec::with_capacity(files.len());
+        let mut snrs = Vec::with_capacity(files.len());
+        for (m, s, rows, frame_snrs) in files {
+            modes.push(m);
+            samples.push(s);
+            source_rows.push(rows);
+            snrs.push(frame_snrs);
+        }
+
         Self {
+            modes,
             samples,
+            source_rows,
+            snrs,
             num_samples,
+            seed,
+            per_mode_seed,
+            dedup_classes: false,
+        }
+    }
+
+    /// Like `read`, but the blocking HDF5 work for each file runs on tokio's
+    /// blocking thread pool, and the files are read concurrently.
+    #[cfg(feature = "async")]
+    pub async fn read_async(
+        folders_from_root: &[&str],
+        num_samples: usize,
+    ) -> Result<Self, String> {
+        let folders = folders_from_root
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>();
+
+        let tasks = ModulationMode::variants()
+            .into_iter()
+            .map(|m| {
+                let folders = folders.clone();
+                tokio::task::spawn_blocking(move || {
+                    let folders = folders.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+                    let handle = try_open_hdf5_file(&folders, m.data_name())?;
+                    let file = RadioFile::new(handle, m, num_samples)?;
+                    Ok::<_, String>(file.into_part())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut files = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let file = task
+                .await
+                .map_err(|reason| format!("Reading task failed because {}", reason))??;
+            files.push(file);
+        }
+
+        Ok(Self::from_parts(files, num_samples, DEFAULT_SEED, false))
+    }
+
+    /// Checks that every mode holds one frame per source row, with the
+    /// same frame length and two channels as the first mode, and that each
+    /// SNR level it has frames from holds `num_samples` of them.
+    ///
+    /// Levels left out of the read, e.g. by `RadioReader::snr_range`, hold
+    /// no frames and are not checked.
+    pub fn validate_sampled(&self) -> Result<(), ValidationError> {
+        let frame_len = self.samples.first().map_or(1024, |s| s.shape()[1]);
+        for (((&m, samples), rows), snrs) in self
+            .modes
+            .iter()
+            .zip(&self.samples)
+            .zip(&self.source_rows)
+            .zip(&self.snrs)
+        {
+            let shape_error = |snr, expected: usize, actual: usize| ValidationError::Shape {
+                modulation: Some(m),
+                snr,
+                expected: vec![expected, frame_len, 2],
+                actual: vec![actual, samples.shape()[1], samples.shape()[2]],
+            };
+            if samples.shape() != [rows.len(), frame_len, 2] {
+                return Err(shape_error(None, rows.len(), samples.shape()[0]));
+            }
+            if snrs.len() != rows.len() {
+                return Err(shape_error(None, snrs.len(), rows.len()));
+            }
+
+            let mut per_level = BTreeMap::new();
+            snrs.iter()
+                .for_each(|&snr| *per_level.entry(snr).or_insert(0) += 1);
+            if let Some((&snr, &count)) = per_level.iter().find(|(_, c)| **c != self.num_samples) {
+                return Err(shape_error(Some(snr), self.num_samples, count));
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `validate_sampled`, but panics on a mismatch.
+    pub fn expect_valid(&self) {
+        if let Err(error) = self.validate_sampled() {
+            panic!("{}", error);
+        }
+    }
+
+    pub fn join(self) -> Array3<f64> {
+        self.concatenated()
+    }
+
+    /// Like `join`, but guaranteed to be in standard (C-contiguous,
+    /// row-major) layout, so that the array can be handed to BLAS or other
+    /// external code as a flat buffer via `as_slice`.
+    ///
+    /// This only copies if the joined array is not already contiguous.
+    pub fn join_contiguous(self) -> Array3<f64> {
+        let joined = self.concatenated();
+        if joined.is_standard_layout() {
+            joined
+        } else {
+            joined.as_standard_layout().into_owned()
+        }
+    }
+
+    /// Like `join`, but with each frame flattened into one row, giving an
+    /// `(n_frames, 2048)` matrix. I and Q are interleaved within each row as
+    /// `[i_0, q_0, i_1, q_1, ...]`.
+    pub fn join_flat(self) -> Array2<f64> {
+        let joined = self.concatenated();
+        let (n, frame_len, channels) = joined.dim();
+        joined.into_shape((n, frame_len * channels)).unwrap()
+    }
+
+    /// Like `join`, but stacking the modes along a new first axis instead of
+    /// concatenating them, giving `(n_modes, n_frames_per_mode, 1024, 2)`
+    /// in the order of the modes read.
+    ///
+    /// Fails if the modes have different frame counts, e.g. since the noise
+    /// file has a single level where the other modes have 26.
+    pub fn stack_modes(self) -> Result<Array4<f64>, String> {
+        if let Some((i, s)) = self
+            .samples
+            .iter()
+            .enumerate()
+            .find(|(_, s)| s.shape()[0] != self.samples[0].shape()[0])
+        {
+            return Err(format!(
+                "Cannot stack modes with different frame counts: {:?} has {} but {:?} has {}",
+                self.modes[0],
+                self.samples[0].shape()[0],
+                self.modes[i],
+                s.shape()[0]
+            ));
+        }
+        let samples = self.samples.iter().map(|s| s.view()).collect::<Vec<_>>();
+        ndarray::stack(Axis(0), &samples)
+            .map_err(|reason| format!("Could not stack modes because {}", reason))
+    }
+
+    /// The modes, seed and sampled rows this data was read with.
+    pub fn meta(&self) -> RadioDataMeta {
+        RadioDataMeta {
+            modes: self.modes.clone(),
+            num_samples: self.num_samples,
+            seed: self.seed,
+            per_mode_seed: self.per_mode_seed,
+            dedup_classes: self.dedup_classes,
+            source_rows: self.source_rows.clone(),
+        }
+    }
+
+    /// The number of frames of each mode at each SNR, without touching the
+    /// samples. Cells with no frames are absent.
+    pub fn frame_census(&self) -> BTreeMap<(ModulationMode, i32), usize> {
+        let mut census = BTreeMap::new();
+        for (&m, snrs) in self.modes.iter().zip(self.snrs.iter()) {
+            for &snr in snrs {
+                *census.entry((m, snr)).or_insert(0) += 1;
+            }
+        }
+        census
+    }
+
+    /// SNR label of each frame, in `join` order.
+    pub fn snrs(&self) -> Array1<i32> {
+        self.snrs.iter().flatten().copied().collect()
+    }
+
+    /// The number of frames that `join` would return, without joining.
+    pub fn num_frames(&self) -> usize {
+        self.samples.iter().map(|s| s.shape()[0]).sum()
+    }
+
+    /// The shape of the array that `join` would return, without joining.
+    /// Multiply by 8 bytes for the memory it would take.
+    pub fn joined_shape(&self) -> [usize; 3] {
+        let (frame_len, channels) = self
+            .samples
+            .first()
+            .map_or((1024, 2), |s| (s.shape()[1], s.shape()[2]));
+        [self.num_frames(), frame_len, channels]
+    }
+
+    /// The label of each frame in `join` order, as the index of its
+    /// modulation mode, or of its `canonical_class` with `dedup_classes`.
+    pub fn labels(&self) -> Array1<usize> {
+        if self.dedup_classes {
+            let classes = self
+                .modes
+                .iter()
+                .map(|m| m.canonical_class())
+                .collect::<Vec<_>>();
+            labels_of(&classes, &self.samples)
+        } else {
+            labels_of(&self.modes, &self.samples)
+        }
+    }
+
+    /// Whether to label `OOK_2` frames as `OOK`, merging the two files into
+    /// one class. Off by default, when each file is its own class.
+    ///
+    /// This only changes `labels` and what is derived from them. The frames
+    /// stay under their own mode, e.g. for `samples_for`.
+    pub fn dedup_classes(mut self, dedup_classes: bool) -> Self {
+        self.dedup_classes = dedup_classes;
+        self
+    }
+
+    /// The samples read for `mode`, or `None` if that mode was not read.
+    pub fn samples_for(&self, mode: ModulationMode) -> Option<ArrayView3<f64>> {
+        self.modes
+            .iter()
+            .position(|&m| m == mode)
+            .map(|i| self.samples[i].view())
+    }
+
+    /// The row in the original file's `X` dataset of each frame sampled for
+    /// `mode`, in the same order as `samples_for`, or `None` if that mode was
+    /// not read.
+    pub fn source_rows_for(&self, mode: ModulationMode) -> Option<&[usize]> {
+        self.modes
+            .iter()
+            .position(|&m| m == mode)
+            .map(|i| self.source_rows[i].as_slice())
+    }
+
+    /// Yields the frames from `join`, with their labels, in chunks of
+    /// `batch_size` without allocating the full concatenation. The final
+    /// batch may be smaller.
+    pub fn batches(
+        &self,
+        batch_size: usize,
+    ) -> impl Iterator<Item = (Array3<f64>, Array1<usize>)> + '_ {
+        assert!(batch_size > 0, "batch_size must be positive");
+        let labels = self.labels();
+        let num_frames = labels.len();
+        (0..num_frames).step_by(batch_size).map(move |start| {
+            let end = (start + batch_size).min(num_frames);
+            let mut offset = 0;
+            let mut parts = Vec::new();
+            for block in &self.samples {
+                let n = block.shape()[0];
+                let (lo, hi) = (start.max(offset), end.min(offset + n));
+                if lo < hi {
+                    parts.push(block.slice(s![lo - offset..hi - offset, .., ..]));
+                }
+                offset += n;
+            }
+            let frames = ndarray::concatenate(Axis(0), &parts).unwrap();
+            (frames, labels.slice(s![start..end]).to_owned())
+        })
+    }
+
+    /// Writes the joined `(n_frames, 1024, 2)` samples to a `.npy` file.
+    pub fn write_npy(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        ndarray_npy::write_npy(path, &self.concatenated())
+            .map_err(|reason| format!("Could not write {:?} because {}", path, reason))
+    }
+
+    /// Writes the labels from `labels` to a `.npy` file as `int64`.
+    pub fn write_labels_npy(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let labels = self.labels().mapv(|l| l as i64);
+        ndarray_npy::write_npy(path, &labels)
+            .map_err(|reason| format!("Could not write {:?} because {}", path, reason))
+    }
+
+    /// Magnitude spectra of every frame, as an `(n_frames, 1024)` matrix in
+    /// `join` order.
+    pub fn spectra(&self) -> Array2<f64> {
+        let spectra = self
+            .samples
+            .iter()
+            .map(|s| crate::spectrum::spectra(s.view()))
+            .collect::<Vec<_>>();
+        let spectra = spectra.iter().map(|s| s.view()).collect::<Vec<_>>();
+        ndarray::concatenate(Axis(0), &spectra).unwrap()
+    }
+
+    /// Applies the feature extractor `f` to every frame in parallel, giving
+    /// an `(n_frames, N)` matrix in `join` order, i.e. aligned with `labels`.
+    pub fn extract_features<F, const N: usize>(&self, f: F) -> Array2<f64>
+    where
+        F: Fn(ArrayView2<f64>) -> [f64; N] + Sync,
+    {
+        let features = self
+            .samples
+            .iter()
+            .flat_map(|s| {
+                (0..s.shape()[0])
+                    .into_par_iter()
+                    .map(|i| f(s.index_axis(Axis(0), i)))
+                    .collect::<Vec<_>>()
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        Array2::from_shape_vec((self.num_frames(), N), features).unwrap()
+    }
+
+    /// The index in `join` order and `FrameQuality` of every frame that
+    /// `FrameQuality::is_suspicious` flags, e.g. all-zero or clipped frames
+    /// from a corrupt export.
+    pub fn quality_report(&self) -> Vec<(usize, FrameQuality)> {
+        self.samples
+            .iter()
+            .flat_map(|s| s.outer_iter())
+            .map(quality::frame_quality)
+            .enumerate()
+            .filter(|(_, q)| q.is_suspicious())
+            .collect()
+    }
+
+    /// Flattens every frame into a `Vec<f64>` of 2048 interleaved IQ values
+    /// and wraps them in a CLAM dataset, using DTW over the joint IQ samples
+    /// as the metric. The labels are returned alongside, in the same order.
+    pub fn into_clam_dataset(self) -> (clam::VecDataset<Vec<f64>, f32>, Array1<usize>) {
+        let labels = self.labels();
+        let frames = self
+            .samples
+            .iter()
+            .flat_map(|s| {
+                s.outer_iter()
+                    .map(|f| f.iter().copied().collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        let dataset = clam::VecDataset::new("radio_ml".to_string(), frames, dtw_iq, true);
+        (dataset, labels)
+    }
+
+    /// Caches the sampled data in an HDF5 file that `load` can read back.
+    ///
+    /// The file holds the mode indices under `modes`, `num_samples`, and one
+    /// `samples_{i}` and `source_rows_{i}` dataset per mode.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let to_string =
+            |reason: hdf5::Error| format!("Could not write {:?} because {}", path, reason);
+
+        let file = hdf5::File::create(path).map_err(to_string)?;
+        let modes = self
+            .modes
+            .iter()
+            .map(|m| m.index() as u64)
+            .collect::<Array1<_>>();
+        file.new_dataset_builder()
+            .with_data(&modes)
+            .create("modes")
+            .map_err(to_string)?;
+        file.new_dataset::<u64>()
+            .create("num_samples")
+            .and_then(|d| d.write_scalar(&(self.num_samples as u64)))
+            .map_err(to_string)?;
+        file.new_dataset::<u64>()
+            .create("seed")
+            .and_then(|d| d.write_scalar(&self.seed))
+            .map_err(to_string)?;
+        file.new_dataset::<u8>()
+            .create("per_mode_seed")
+            .and_then(|d| d.write_scalar(&u8::from(self.per_mode_seed)))
+            .map_err(to_string)?;
+        file.new_dataset::<u8>()
+            .create("dedup_classes")
+            .and_then(|d| d.write_scalar(&u8::from(self.dedup_classes)))
+            .map_err(to_string)?;
+        for (i, (samples, rows)) in self.samples.iter().zip(self.source_rows.iter()).enumerate() {
+            file.new_dataset_builder()
+                .with_data(samples)
+                .create(format!("samples_{}", i).as_str())
+                .map_err(to_string)?;
+            let rows = rows.iter().map(|&r| r as u64).collect::<Array1<_>>();
+            file.new_dataset_builder()
+                .with_data(&rows)
+                .create(format!("source_rows_{}", i).as_str())
+                .map_err(to_string)?;
+            file.new_dataset_builder()
+                .with_data(&Array1::from(self.snrs[i].clone()))
+                .create(format!("snrs_{}", i).as_str())
+                .map_err(to_string)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the joined frames to a new HDF5 file at `path`, under
+    /// `dataset_name`, with one-hot `labels` over `ModulationMode::variants`
+    /// under `Y` and an `(n_frames, 1)` column of SNRs under `Z`.
+    ///
+    /// With `dataset_name` `X` this is the layout of the original files, so
+    /// data of a single mode written to its `data_name` in a folder reads
+    /// back with `RadioReader`, reproducing the frames for the same
+    /// `num_samples` since sampled frames are kept in file order. All three
+    /// datasets are chunked and written one mode at a time, so the joined
+    /// arrays are never held in memory.
+    pub fn write_hdf5(&self, path: impl AsRef<Path>, dataset_name: &str) -> Result<(), String> {
+        let path = path.as_ref();
+        let to_string =
+            |reason: hdf5::Error| format!("Could not write {:?} because {}", path, reason);
+
+        let [num_frames, frame_len, channels] = self.joined_shape();
+        let num_classes = ModulationMode::variants().len();
+        let chunk_rows = num_frames.clamp(1, 256);
+        let file = hdf5::File::create(path).map_err(to_string)?;
+        let frames = file
+            .new_dataset::<f64>()
+            .chunk((chunk_rows, frame_len, channels))
+            .shape((num_frames, frame_len, channels))
+            .create(dataset_name)
+            .map_err(to_string)?;
+        let onehot = file
+            .new_dataset::<f64>()
+            .chunk((chunk_rows, num_classes))
+            .shape((num_frames, num_classes))
+            .create("Y")
+            .map_err(to_string)?;
+        let z = file
+            .new_dataset::<f64>()
+            .chunk((chunk_rows, 1))
+            .shape((num_frames, 1))
+            .create("Z")
+            .map_err(to_string)?;
+
+        let labels = self.labels();
+        let mut start = 0;
+        for (block, snrs) in self.samples.iter().zip(&self.snrs) {
+            let end = start + block.shape()[0];
+            frames
+                .write_slice(block, s![start..end, .., ..])
+                .map_err(to_string)?;
+
+            let mut block_onehot = Array2::<f64>::zeros((end - start, num_classes));
+            for (mut row, &label) in block_onehot
+                .outer_iter_mut()
+                .zip(labels.slice(s![start..end]))
+            {
+                row[label] = 1.;
+            }
+            onehot
+                .write_slice(&block_onehot, s![start..end, ..])
+                .map_err(to_string)?;
+
+            let block_z = Array2::from_shape_fn((end - start, 1), |(f, _)| f64::from(snrs[f]));
+            z.write_slice(&block_z, s![start..end, ..])
+                .map_err(to_string)?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Reads back data written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let to_string =
+            |reason: hdf5::Error| format!("Could not read {:?} because {}", path, reason);
+
+        let file = hdf5::File::open(path).map_err(to_string)?;
+        let modes = file
+            .dataset("modes")
+            .and_then(|d| d.read_1d::<u64>())
+            .map_err(to_string)?
+            .iter()
+            .map(|&i| {
+                ModulationMode::from_index(i as usize)
+                    .ok_or_else(|| format!("{:?} has unknown mode index {}", path, i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_samples = file
+            .dataset("num_samples")
+            .and_then(|d| d.read_scalar::<u64>())
+            .map_err(to_string)? as usize;
+        // Files saved before the seed was recorded were read with the default.
+        let (seed, per_mode_seed) = if file.link_exists("seed") {
+            let seed = file
+                .dataset("seed")
+                .and_then(|d| d.read_scalar::<u64>())
+                .map_err(to_string)?;
+            let per_mode_seed = file
+                .dataset("per_mode_seed")
+                .and_then(|d| d.read_scalar::<u8>())
+                .map_err(to_string)?;
+            (seed, per_mode_seed != 0)
+        } else {
+            (DEFAULT_SEED, false)
+        };
+        let dedup_classes = if file.link_exists("dedup_classes") {
+            file.dataset("dedup_classes")
+                .and_then(|d| d.read_scalar::<u8>())
+                .map_err(to_string)?
+                != 0
+        } else {
+            false
+        };
+        let samples = (0..modes.len())
+            .map(|i| {
+                file.dataset(format!("samples_{}", i).as_str())
+                    .and_then(|d| d.read::<f64, Ix3>())
+                    .map_err(to_string)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let source_rows = (0..modes.len())
+            .map(|i| {
+                file.dataset(format!("source_rows_{}", i).as_str())
+                    .and_then(|d| d.read_raw::<u64>())
+                    .map(|rows| rows.into_iter().map(|r| r as usize).collect())
+                    .map_err(to_string)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let snrs = (0..modes.len())
+            .map(|i| {
+                file.dataset(format!("snrs_{}", i).as_str())
+                    .and_then(|d| d.read_raw::<i32>())
+                    .map_err(to_string)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            modes,
+            samples,
+            source_rows,
+            snrs,
+            num_samples,
+            seed,
+            per_mode_seed,
+            dedup_classes,
+        })
+    }
+
+    /// Like `read`, but keeps only the first `frame_len` time steps of each
+    /// frame.
+    pub fn read_truncated(
+        folders_from_root: &[&str],
+        num_samples: usize,
+        frame_len: usize,
+    ) -> Result<Self, String> {
+        let mut data = Self::read(folders_from_root, num_samples);
+        data.truncate(frame_len)?;
+        Ok(data)
+    }
+
+    /// Keeps only the first `len` time steps of each frame.
+    pub fn truncate(&mut self, len: usize) -> Result<(), String> {
+        self.samples = self
+            .samples
+            .iter()
+            .map(|s| truncate_frames(s.view(), len))
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Like `SingleSnR::minmax_normalize`, but with each channel's range
+    /// taken over every frame of every mode.
+    pub fn minmax_normalize(&mut self) -> (Array1<f64>, Array1<f64>) {
+        let (mins, maxs) = channel_ranges(self.samples.iter().map(|s| s.view()));
+        self.samples
+            .iter_mut()
+            .for_each(|s| scale_channels(s, &mins, &maxs));
+        (mins, maxs)
+    }
+
+    fn concatenated(&self) -> Array3<f64> {
+        let samples = self.samples.par_iter().map(|s| s.view()).collect::<Vec<_>>();
+        ndarray::concatenate(Axis(0), &samples).unwrap()
+    }
+}
+
+/// Options for reading a `RadioData`, defaulting to what `read` does.
+///
+/// ```ignore
+/// let data = RadioReader::new("/data/rf")
+///     .modes(&[ModulationMode::QPSK, ModulationMode::BPSK])
+///     .num_samples(50)
+///     .snr_range(0, 30)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RadioReader {
+    base_path: PathBuf,
+    modes: Vec<ModulationMode>,
+    num_samples: usize,
+    seed: u64,
+    per_mode_seed: bool,
+    snrs: RangeInclusive<i32>,
+    dedup_classes: bool,
+}
+
+impl RadioReader {
+    /// Reads from the files in `base_path`, by default 100 frames from
+    /// every SNR level of every mode, sampled with `DEFAULT_SEED`.
+    pub fn new(base_path: impl AsRef<Path>) -> Self {
+        Self {
+            base_path: base_path.as_ref().to_path_buf(),
+            modes: ModulationMode::variants().to_vec(),
+            num_samples: 100,
+            seed: DEFAULT_SEED,
+            per_mode_seed: false,
+            snrs: ALL_SNRS,
+            dedup_classes: false,
+        }
+    }
+
+    /// Reads only `modes`, in the given order.
+    pub fn modes(mut self, modes: &[ModulationMode]) -> Self {
+        self.modes = modes.to_vec();
+        self
+    }
+
+    /// The number of frames to sample from each SNR level.
+    pub fn num_samples(mut self, num_samples: usize) -> Self {
+        self.num_samples = num_samples;
+        self
+    }
+
+    /// The seed to sample with.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Whether each mode samples with its own seed, as in `read_seeded`.
+    pub fn per_mode_seed(mut self, per_mode_seed: bool) -> Self {
+        self.per_mode_seed = per_mode_seed;
+        self
+    }
+
+    /// Samples only the SNR levels from `min_db` to `max_db` inclusive.
+    /// This drops the noise file's frames unless the range contains
+    /// `NOISE_SNR_DB`.
+    pub fn snr_range(mut self, min_db: i32, max_db: i32) -> Self {
+        self.snrs = min_db..=max_db;
+        self
+    }
+
+    /// Whether to label `OOK_2` frames as `OOK`, as in
+    /// `RadioData::dedup_classes`.
+    pub fn dedup_classes(mut self, dedup_classes: bool) -> Self {
+        self.dedup_classes = dedup_classes;
+        self
+    }
+
+    /// Opens the file of each mode and samples it.
+    pub fn build(self) -> Result<RadioData, String> {
+        let handles = self
+            .modes
+            .iter()
+            .map(|&m| {
+                let path = self.base_path.join(m.data_name());
+                hdf5::File::open(&path)
+                    .map(|handle| (m, handle))
+                    .map_err(|reason| format!("Could not open {:?} because {}", path, reason))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        RadioData::sample_handles(
+            handles,
+            self.num_samples,
+            self.seed,
+            self.per_mode_seed,
+            self.snrs,
+            |_, _, _| (),
+        )
+        .map(|data| data.dedup_classes(self.dedup_classes))
+    }
+}
+
+/// A complex sample as h5py stores it, a compound of real and imaginary
+/// parts. HDF5 converts `complex64` files to this on read.
+#[derive(hdf5::H5Type, Clone, Copy, Debug)]
+#[repr(C)]
+struct H5Complex {
+    r: f64,
+    i: f64,
+}
+
+/// Whether `dataset` stores complex samples, e.g. `X` of shape
+/// `[_, 1024]` in a re-exported file, rather than the usual real layout with
+/// a trailing axis of I and Q.
+fn is_complex(dataset: &hdf5::Dataset) -> bool {
+    matches!(
+        dataset.dtype().and_then(|dtype| dtype.to_descriptor()),
+        Ok(hdf5::types::TypeDescriptor::Compound(_))
+    )
+}
+
+/// Reads a dataset of complex samples with shape `(n_frames, frame_len)`.
+/// `iq::from_complex` converts each frame to the real layout.
+pub fn read_complex(dataset: &hdf5::Dataset) -> Result<Array2<Complex<f64>>, String> {
+    let signal = dataset.read::<H5Complex, Ix2>().map_err(|reason| {
+        format!(
+            "Could not convert from HDF5 to Array2<Complex<f64>> because {}",
+            reason
+        )
+    })?;
+    Ok(signal.mapv(|z| Complex::new(z.r, z.i)))
+}
+
+/// The shape of `X` in the real `(n_frames, frame_len, 2)` layout, whether
+/// it stores that layout or complex samples.
+fn real_shape(dataset: &hdf5::Dataset) -> Vec<usize> {
+    let mut shape = dataset.shape();
+    if is_complex(dataset) {
+        shape.push(2);
+    }
+    shape
+}
+
+/// Reads the frames at `rows` of `X` in the real `(n_frames, frame_len, 2)`
+/// layout, converting complex samples.
+fn read_rows(dataset: &hdf5::Dataset, rows: Range<usize>) -> Result<Array3<f64>, String> {
+    if is_complex(dataset) {
+        let signal: Array2<H5Complex> = dataset.read_slice(s![rows, ..]).map_err(|reason| {
+            format!(
+                "Could not convert from HDF5 to Array2<Complex<f64>> because {}",
+                reason
+            )
+        })?;
+        Ok(split_complex(
+            signal.mapv(|z| Complex::new(z.r, z.i)).view(),
+        ))
+    } else {
+        dataset.read_slice(s![rows, .., ..]).map_err(|reason| {
+            format!(
+                "Could not convert from HDF5 to Array3<f64> because {}",
+                reason
+            )
+        })
+    }
+}
+
+/// Complex frames of shape `(n_frames, frame_len)` in the real
+/// `(n_frames, frame_len, 2)` layout.
+fn split_complex(signal: ArrayView2<Complex<f64>>) -> Array3<f64> {
+    Array3::from_shape_fn((signal.nrows(), signal.ncols(), 2), |(f, t, c)| {
+        let z = signal[[f, t]];
+        if c == 0 {
+            z.re
+        } else {
+            z.im
+        }
+    })
+}
+
+/// The inverse of `SingleSnR::quantize_i8`.
+pub fn dequantize(quantized: ArrayView3<i8>, scale: f64, zero_point: f64) -> Array3<f64> {
+    quantized.mapv(|q| (f64::from(q) - zero_point) * scale)
+}
+
+/// The minimum and maximum of each channel over all of `blocks`.
+fn channel_ranges<'a>(
+    blocks: impl Iterator<Item = ArrayView3<'a, f64>>,
+) -> (Array1<f64>, Array1<f64>) {
+    let mut mins = Array1::from_elem(2, f64::INFINITY);
+    let mut maxs = Array1::from_elem(2, f64::NEG_INFINITY);
+    for block in blocks {
+        for (c, channel) in block.axis_iter(Axis(2)).enumerate() {
+            mins[c] = channel.fold(mins[c], |m, &v| m.min(v));
+            maxs[c] = channel.fold(maxs[c], |m, &v| m.max(v));
         }
     }
+    (mins, maxs)
+}
 
-    pub fn validate_sampled(&self) {
-        ModulationMode::variants()
-            .par_iter()
-            .zip(self.samples.par_iter())
-            .for_each(|(_, s)| assert_eq!([26 * self.num_samples, 1024, 2], s.shape()));
+/// Maps each channel of `iq` from `[mins[c], maxs[c]]` to `[0, 1]`, or to
+/// 0.5 where the channel is constant.
+fn scale_channels(iq: &mut Array3<f64>, mins: &Array1<f64>, maxs: &Array1<f64>) {
+    for (c, mut channel) in iq.axis_iter_mut(Axis(2)).enumerate() {
+        let range = maxs[c] - mins[c];
+        if range > 0. {
+            channel.mapv_inplace(|v| (v - mins[c]) / range);
+        } else {
+            channel.fill(0.5);
+        }
     }
+}
 
-    pub fn join(self) -> Array3<f64> {
-        let samples = self.samples.par_iter().map(|s| s.view()).collect::<Vec<_>>();
-        ndarray::concatenate(Axis(0), &samples).unwrap()
+/// The first `len` time steps of each frame in `(n_frames, frame_len, 2)`.
+fn truncate_frames(iq: ArrayView3<f64>, len: usize) -> Result<Array3<f64>, String> {
+    let frame_len = iq.shape()[1];
+    if len > frame_len {
+        return Err(format!(
+            "Cannot truncate frames of length {} to {}",
+            frame_len, len
+        ));
     }
+    Ok(iq.slice(s![.., ..len, ..]).to_owned())
+}
+
+/// DTW between two flattened frames, warping over `(i, q)` time steps with
+/// the complex Euclidean point distance.
+#[allow(clippy::ptr_arg)]
+fn dtw_iq(x: &Vec<f64>, y: &Vec<f64>) -> f32 {
+    let dtw = DynamicTimeWarping::new(Arc::new(ComplexEuclidean::new()));
+    let x = ArrayView2::from_shape((x.len() / 2, 2), x).unwrap();
+    let y = ArrayView2::from_shape((y.len() / 2, 2), y).unwrap();
+    dtw.one_to_one_multi(x, y)
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(non_camel_case_types)]
 pub enum ModulationMode {
     QAM_64,
@@ -80,7 +1294,7 @@ pub enum ModulationMode {
     QAM_256,
     APSK_128,
     APSK_16,
-    OOK_2, // Should this exist?
+    OOK_2, // A second file of OOK frames, see `canonical_class`.
     BPSK,
     QAM_16,
     AM_SSB_WC,
@@ -88,6 +1302,30 @@ pub enum ModulationMode {
 }
 
 impl ModulationMode {
+    /// Position of this mode in `variants`, used as its integer label.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The inverse of `index`.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::variants().get(index).copied()
+    }
+
+    /// The class this mode's frames belong to. `OOK_2` (`mod2_OOK.h5`) is a
+    /// second file of `OOK` frames, so it maps to `OOK`, and every other mode
+    /// maps to itself.
+    ///
+    /// The two stay distinct variants with their own `index`, since they
+    /// name different files. Use `dedup_classes` on the reader to label
+    /// them as one class.
+    pub fn canonical_class(&self) -> Self {
+        match self {
+            Self::OOK_2 => Self::OOK,
+            mode => *mode,
+        }
+    }
+
     pub fn data_name(&self) -> &str {
         match self {
             Self::QAM_64 => "mod_64QAM.h5",
@@ -142,7 +1380,7 @@ impl ModulationMode {
             Self::QAM_256,
             Self::APSK_128,
             Self::APSK_16,
-            Self::OOK_2, // Should this exist?
+            Self::OOK_2, // A second file of OOK frames, see `canonical_class`.
             Self::BPSK,
             Self::QAM_16,
             Self::AM_SSB_WC,
@@ -151,11 +1389,111 @@ impl ModulationMode {
     }
 }
 
+/// A set of modulation classes, such as `ModulationMode` for the 2018
+/// dataset or `Mode2016` for the 2016 one, that frames can be labeled with.
+pub trait ModulationSet: Copy + Eq + std::fmt::Debug {
+    /// The integer label of this class.
+    fn label(&self) -> usize;
+}
+
+impl ModulationSet for ModulationMode {
+    fn label(&self) -> usize {
+        self.index()
+    }
+}
+
+/// The label of each frame in `samples` concatenated in order, where
+/// `samples[i]` holds frames of class `modes[i]`.
+pub(crate) fn labels_of<M: ModulationSet>(modes: &[M], samples: &[Array3<f64>]) -> Array1<usize> {
+    modes
+        .iter()
+        .zip(samples.iter())
+        .flat_map(|(m, s)| std::iter::repeat(m.label()).take(s.shape()[0]))
+        .collect()
+}
+
+impl std::str::FromStr for ModulationMode {
+    type Err = String;
+
+    /// Parses either the variant name, e.g. `QAM_64`, or the name used in the
+    /// data file, e.g. `64QAM`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::variants()
+            .into_iter()
+            .find(|m| {
+                let stem = m.data_name().trim_end_matches(".h5");
+                let stem = stem.split_once('_').map_or(stem, |(_, name)| name);
+                format!("{:?}", m).eq_ignore_ascii_case(s) || stem.eq_ignore_ascii_case(s)
+            })
+            .ok_or_else(|| format!("{:?} is not a modulation mode", s))
+    }
+}
+
+/// Draws `num_samples` sorted indices from `0..pool_size`.
+///
+/// `choose_multiple` over an iterator is reservoir sampling, so this works
+/// for any pool size and is deterministic for a given seed.
+pub(crate) fn sample_indices(pool_size: usize, num_samples: usize, seed: u64) -> Vec<usize> {
+    let mut indices =
+        (0..pool_size).choose_multiple(&mut ChaCha8Rng::seed_from_u64(seed), num_samples);
+    indices.sort();
+    indices
+}
+
+/// Fails with `TooManySamples` for the first count above `available`.
+/// `choose_multiple` would otherwise silently return fewer frames.
+pub(crate) fn check_sample_counts(counts: &[usize], available: usize) -> Result<(), ReaderError> {
+    match counts.iter().find(|&&c| c > available) {
+        Some(&requested) => Err(ReaderError::TooManySamples {
+            requested,
+            available,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The SNR of each level in the file of `modulation`, in file order. Noise
+/// files are a single level.
+fn level_snrs(modulation: ModulationMode) -> Vec<i32> {
+    if matches!(modulation, ModulationMode::Noise_20220222) {
+        vec![NOISE_SNR_DB]
+    } else {
+        (-20..=30).step_by(2).collect()
+    }
+}
+
+/// The SNR of each level in a file with frames of the given `shape`, and
+/// the number of frames per level. Noise files are a single level.
+fn snr_layout(modulation: ModulationMode, shape: &[usize]) -> Result<(Vec<i32>, usize), String> {
+    if shape.len() != 3 || shape[1..] != [1024, 2] {
+        return Err(format!(
+            "{:?} had data of shape {:?} instead of [_, 1024, 2]",
+            modulation, shape
+        ));
+    }
+
+    let snrs = level_snrs(modulation);
+    if shape[0] % snrs.len() != 0 {
+        return Err(format!(
+            "{:?} had {} frames, which does not split into {} SNR levels",
+            modulation,
+            shape[0],
+            snrs.len()
+        ));
+    }
+    let per_level = shape[0] / snrs.len();
+    Ok((snrs, per_level))
+}
+
 #[derive(Debug)]
 pub struct RadioFile {
     modulation: ModulationMode,
-    levels: Vec<SingleSnR>, // len == 26
-    sample_indices: Vec<usize>,
+    levels: Vec<SingleSnR>,          // len == 26
+    sample_indices: Vec<Vec<usize>>, // one per level
+    frames_per_level: usize,
+    onehot_labels: Option<Array2<f64>>,
+    snr_per_frame: Option<Array1<i32>>,
 }
 
 impl RadioFile {
@@ -163,69 +1501,440 @@ impl RadioFile {
         handle: hdf5::File,
         modulation: ModulationMode,
         num_samples: usize,
-    ) -> Result<Self, String> {
-        let mut all_iq: Array3<f64> = handle
+    ) -> Result<Self, ReaderError> {
+        Self::new_seeded(handle, modulation, num_samples, DEFAULT_SEED)
+    }
+
+    /// Like `new`, but sampling frames with the given `seed`.
+    pub fn new_seeded(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        num_samples: usize,
+        seed: u64,
+    ) -> Result<Self, ReaderError> {
+        Self::read_sampled(handle, modulation, seed, |snrs| {
+            Ok(vec![num_samples; snrs.len()])
+        })
+    }
+
+    /// Like `new_seeded`, but only sampling from the SNR levels in `snrs`,
+    /// leaving the other levels empty. The noise file's level has SNR
+    /// `NOISE_SNR_DB`, so it is only sampled if `snrs` contains that.
+    pub fn new_in_snr_range(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        num_samples: usize,
+        seed: u64,
+        snrs: &RangeInclusive<i32>,
+    ) -> Result<Self, ReaderError> {
+        Self::read_sampled(handle, modulation, seed, |levels| {
+            Ok(levels
+                .iter()
+                .map(|snr| if snrs.contains(snr) { num_samples } else { 0 })
+                .collect())
+        })
+    }
+
+    /// Like `new_seeded`, but sampling `counts[l]` frames from SNR level
+    /// `l`. There must be one count per level (26, or 1 for noise), and no
+    /// count may exceed the number of frames in a level.
+    pub fn new_per_snr(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        counts: &[usize],
+        seed: u64,
+    ) -> Result<Self, ReaderError> {
+        Self::read_sampled(handle, modulation, seed, |snrs| {
+            if counts.len() != snrs.len() {
+                return Err(format!(
+                    "{:?} has {} SNR levels but got {} counts",
+                    modulation,
+                    snrs.len(),
+                    counts.len()
+                ));
+            }
+            Ok(counts.to_vec())
+        })
+    }
+
+    /// Reads all of `X` and samples each SNR level. `counts` is given the
+    /// SNR of each level and returns how many frames to sample from each
+    /// level, none of which may exceed the frames per level.
+    fn read_sampled(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        seed: u64,
+        counts: impl FnOnce(&[i32]) -> Result<Vec<usize>, String>,
+    ) -> Result<Self, ReaderError> {
+        let all_iq = Self::read_frames(&handle)?;
+        Self::sample_frames(all_iq.view(), modulation, seed, counts)
+    }
+
+    /// Reads all of `X` in the real `(n_frames, 1024, 2)` layout.
+    fn read_frames(handle: &hdf5::File) -> Result<Array3<f64>, String> {
+        let dataset = handle
             .dataset("X")
-            .map_err(|reason| format!("Could not read `X` because {}", reason))?
-            .read()
-            .map_err(|reason| {
+            .map_err(|reason| format!("Could not read `X` because {}", reason))?;
+        let all_iq = if is_complex(&dataset) {
+            split_complex(read_complex(&dataset)?.view())
+        } else {
+            dataset.read::<f64, Ix3>().map_err(|reason| {
                 format!(
                     "Could not convert from HDF5 to Array3<f64> because {}",
                     reason
                 )
-            })?;
-
-        let sample_indices = {
-            let mut indices =
-                (0..4096).choose_multiple(&mut ChaCha8Rng::seed_from_u64(42), num_samples);
-            indices.sort();
-            indices
+            })?
         };
+        Ok(all_iq)
+    }
+
+    /// Samples each SNR level of `all_iq`, all of a file's `X`, as
+    /// `read_sampled` does.
+    fn sample_frames(
+        all_iq: ArrayView3<f64>,
+        modulation: ModulationMode,
+        seed: u64,
+        counts: impl FnOnce(&[i32]) -> Result<Vec<usize>, String>,
+    ) -> Result<Self, ReaderError> {
+        let (snrs, frames_per_level) = snr_layout(modulation, all_iq.shape())?;
+        let counts = counts(&snrs)?;
+        check_sample_counts(&counts, frames_per_level)?;
+        let sample_indices = counts
+            .into_iter()
+            .map(|c| sample_indices(frames_per_level, c, seed))
+            .collect::<Vec<_>>();
 
-        let levels = if matches!(modulation, ModulationMode::Noise_20220222) {
-            assert_eq!(
-                all_iq.shape(),
-                [16_384, 1024, 2],
-                "noise data had the wrong shape"
-            );
+        // Select each level's frames straight out of `X`, so that the sampled
+        // frames are copied exactly once.
+        let mut levels = Vec::new();
+        for (l, (snr, indices)) in snrs.into_iter().zip(sample_indices.iter()).enumerate() {
+            let block = all_iq.slice(s![l * frames_per_level..(l + 1) * frames_per_level, .., ..]);
+            let iq = block.select(Axis(0), indices);
+            levels.push(SingleSnR { iq, snr });
+        }
 
-            vec![SingleSnR {
-                iq: all_iq,
-                snr: -50,
-            }
-            .subsample(&sample_indices)]
-        } else {
-            assert_eq!(
-                all_iq.shape(),
-                [106_496, 1024, 2],
-                "iq data had the wrong shape"
-            );
-
-            let mut all_iq = all_iq.view_mut();
-            let mut levels = Vec::new();
-            for snr in (-20..=30).step_by(2) {
-                let (iq, rest) = all_iq.split_at(Axis(0), 4096);
-                all_iq = rest;
-                let iq = iq.to_owned();
-                levels.push(SingleSnR { iq, snr }.subsample(&sample_indices));
+        Ok(Self {
+            modulation,
+            levels,
+            sample_indices,
+            frames_per_level,
+            onehot_labels: None,
+            snr_per_frame: None,
+        })
+    }
+
+    /// Reads only the first `k` frames of `X` with a single hyperslab,
+    /// without sampling, e.g. for smoke tests on small fixture files. This
+    /// covers only the first levels when `k` is less than the number of
+    /// frames, and later levels are left empty.
+    pub fn new_head(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        k: usize,
+    ) -> Result<Self, ReaderError> {
+        let dataset = handle
+            .dataset("X")
+            .map_err(|reason| format!("Could not read `X` because {}", reason))?;
+        let (snrs, frames_per_level) = snr_layout(modulation, &real_shape(&dataset))?;
+        let available = dataset.shape()[0];
+        if k > available {
+            return Err(ReaderError::TooManySamples {
+                requested: k,
+                available,
+            });
+        }
+        let head = read_rows(&dataset, 0..k)?;
+
+        let mut levels = Vec::with_capacity(snrs.len());
+        let mut sample_indices = Vec::with_capacity(snrs.len());
+        for (l, snr) in snrs.into_iter().enumerate() {
+            let start = (l * frames_per_level).min(k);
+            let end = ((l + 1) * frames_per_level).min(k);
+            let iq = head.slice(s![start..end, .., ..]).to_owned();
+            levels.push(SingleSnR { iq, snr });
+            sample_indices.push((0..end - start).collect());
+        }
+
+        Ok(Self {
+            modulation,
+            levels,
+            sample_indices,
+            frames_per_level,
+            onehot_labels: None,
+            snr_per_frame: None,
+        })
+    }
+
+    /// Reads exactly the frames at rows `indices` of `X`, without sampling,
+    /// e.g. to inspect particular frames or to reproduce an earlier draw
+    /// from its `source_rows`.
+    ///
+    /// Each frame goes to the level its row falls in, keeping the order of
+    /// `indices` within a level, so `join` has the frames sorted by level
+    /// and `source_rows` gives their rows in that order. Duplicate indices
+    /// are read more than once.
+    pub fn new_indices(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        indices: &[usize],
+    ) -> Result<Self, ReaderError> {
+        let dataset = handle
+            .dataset("X")
+            .map_err(|reason| format!("Could not read `X` because {}", reason))?;
+        let (snrs, frames_per_level) = snr_layout(modulation, &real_shape(&dataset))?;
+        let available = dataset.shape()[0];
+        if let Some(&i) = indices.iter().find(|&&i| i >= available) {
+            return Err(ReaderError::Other(format!(
+                "Frame {} is out of range for {:?}, which has {} frames",
+                i, modulation, available
+            )));
+        }
+
+        let mut sample_indices = vec![Vec::new(); snrs.len()];
+        let mut frames = vec![Vec::new(); snrs.len()];
+        for &i in indices {
+            let frame = read_rows(&dataset, i..i + 1)?.index_axis_move(Axis(0), 0);
+            sample_indices[i / frames_per_level].push(i % frames_per_level);
+            frames[i / frames_per_level].push(frame);
+        }
+
+        let levels = snrs
+            .into_iter()
+            .zip(frames)
+            .map(|(snr, frames)| {
+                let views = frames.iter().map(|f| f.view()).collect::<Vec<_>>();
+                let iq = if views.is_empty() {
+                    Array3::zeros((0, 1024, 2))
+                } else {
+                    ndarray::stack(Axis(0), &views).unwrap()
+                };
+                SingleSnR { iq, snr }
+            })
+            .collect();
+
+        Ok(Self {
+            modulation,
+            levels,
+            sample_indices,
+            frames_per_level,
+            onehot_labels: None,
+            snr_per_frame: None,
+        })
+    }
+
+    /// Like `new`, but reads `X` in chunks of at most `buffer_rows` frames,
+    /// keeping only the sampled frames from each chunk. This bounds peak
+    /// memory to roughly `buffer_rows * 16 KiB` on top of the samples, at
+    /// the cost of more HDF5 reads. Chunks holding no sampled frames are
+    /// skipped entirely.
+    pub fn new_buffered(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        num_samples: usize,
+        buffer_rows: usize,
+    ) -> Result<Self, ReaderError> {
+        if buffer_rows == 0 {
+            return Err(ReaderError::Other(
+                "buffer_rows must be positive".to_string(),
+            ));
+        }
+        let dataset = handle
+            .dataset("X")
+            .map_err(|reason| format!("Could not read `X` because {}", reason))?;
+        let (snrs, frames_per_level) = snr_layout(modulation, &real_shape(&dataset))?;
+        check_sample_counts(&[num_samples], frames_per_level)?;
+        let indices = sample_indices(frames_per_level, num_samples, DEFAULT_SEED);
+        let sample_indices = vec![indices; snrs.len()];
+
+        let mut levels = Vec::new();
+        for (l, &snr) in snrs.iter().enumerate() {
+            let level_start = l * frames_per_level;
+            let mut remaining = sample_indices[l].as_slice();
+            let mut chunks = Vec::new();
+            for chunk_start in (0..frames_per_level).step_by(buffer_rows) {
+                let chunk_end = (chunk_start + buffer_rows).min(frames_per_level);
+                let num_inside = remaining.iter().take_while(|&&i| i < chunk_end).count();
+                if num_inside == 0 {
+                    continue;
+                }
+                let (inside, rest) = remaining.split_at(num_inside);
+                remaining = rest;
+
+                let chunk =
+                    read_rows(&dataset, level_start + chunk_start..level_start + chunk_end)?;
+                let local = inside.iter().map(|&i| i - chunk_start).collect::<Vec<_>>();
+                chunks.push(chunk.select(Axis(0), &local));
             }
 
-            levels
-        };
+            let iq = if chunks.is_empty() {
+                Array3::zeros((0, 1024, 2))
+            } else {
+                let chunks = chunks.iter().map(|c| c.view()).collect::<Vec<_>>();
+                ndarray::concatenate(Axis(0), &chunks).unwrap()
+            };
+            levels.push(SingleSnR { iq, snr });
+        }
 
         Ok(Self {
             modulation,
             levels,
             sample_indices,
+            frames_per_level,
+            onehot_labels: None,
+            snr_per_frame: None,
         })
     }
 
-    pub fn validate_sampled(&self) {
-        println!("Validating sampled arrays ...");
-        assert_eq!(26, self.levels.len());
+    /// The indices, within each SNR level, of the sampled frames, with one
+    /// list per level.
+    pub fn sample_indices(&self) -> &[Vec<usize>] {
+        &self.sample_indices
+    }
+
+    /// Like `new`, but also reads the `Y` (one-hot labels) and `Z` (SNR per
+    /// frame) datasets for the sampled frames.
+    pub fn new_with_aux(
+        handle: hdf5::File,
+        modulation: ModulationMode,
+        num_samples: usize,
+    ) -> Result<Self, String> {
+        let read_2d = |name: &str| -> Result<Array2<f64>, String> {
+            handle
+                .dataset(name)
+                .map_err(|reason| format!("Could not read `{}` because {}", name, reason))?
+                .read()
+                .map_err(|reason| {
+                    format!(
+                        "Could not convert `{}` from HDF5 to Array2<f64> because {}",
+                        name, reason
+                    )
+                })
+        };
+        let y = read_2d("Y")?;
+        let z = read_2d("Z")?;
+
+        let mut file = Self::new(handle, modulation, num_samples)?;
+        let rows = file.source_rows();
+        file.onehot_labels = Some(y.select(Axis(0), &rows));
+        file.snr_per_frame = Some(
+            z.select(Axis(0), &rows)
+                .iter()
+                .map(|&snr| snr.round() as i32)
+                .collect(),
+        );
+        Ok(file)
+    }
+
+    /// The one-hot labels from `Y` for each sampled frame, in `join` order.
+    /// Only present when read with `new_with_aux`.
+    pub fn onehot_labels(&self) -> Option<Array2<f64>> {
+        self.onehot_labels.clone()
+    }
+
+    /// The SNR from `Z` for each sampled frame, in `join` order. Only present
+    /// when read with `new_with_aux`.
+    pub fn snr_per_frame(&self) -> Option<Array1<i32>> {
+        self.snr_per_frame.clone()
+    }
+
+    /// Checks that the file's `Z` dataset agrees with the SNR levels derived
+    /// from the frame layout, i.e. that each block of frames has a single
+    /// SNR equal to that level's. The noise file has no SNR levels, so only
+    /// the constancy of its block is checked.
+    pub fn verify_snr_layout(&self, handle: &hdf5::File) -> Result<(), String> {
+        let z: Array2<f64> = handle
+            .dataset("Z")
+            .map_err(|reason| format!("Could not read `Z` because {}", reason))?
+            .read()
+            .map_err(|reason| {
+                format!(
+                    "Could not convert `Z` from HDF5 to Array2<f64> because {}",
+                    reason
+                )
+            })?;
+
+        let num_frames = self.levels.len() * self.frames_per_level;
+        if z.nrows() < num_frames || z.ncols() == 0 {
+            return Err(format!(
+                "{:?} has `Z` of shape {:?} but {} frames in `X`",
+                self.modulation,
+                z.shape(),
+                num_frames
+            ));
+        }
+
+        let is_noise = matches!(self.modulation, ModulationMode::Noise_20220222);
+        for (l, level) in self.levels.iter().enumerate() {
+            let start = l * self.frames_per_level;
+            let block = z.slice(s![start..start + self.frames_per_level, 0]);
+            let expected = match block.first() {
+                Some(&snr) if is_noise => snr.round() as i32,
+                Some(_) => level.snr,
+                None => continue,
+            };
+            if let Some(row) = block.iter().position(|&snr| snr.round() as i32 != expected) {
+                return Err(format!(
+                    "{:?} block {} (frames {}..{}) should have SNR {}dB but frame {} has {}dB",
+                    self.modulation,
+                    l,
+                    start,
+                    start + self.frames_per_level,
+                    expected,
+                    start + row,
+                    block[row]
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Relabels the noise file's level with `snr_db` instead of
+    /// `NOISE_SNR_DB`, e.g. for a sentinel that cannot be mistaken for a
+    /// real SNR. Files of other modes are returned unchanged.
+    pub fn with_noise_snr_db(mut self, snr_db: i32) -> Self {
+        if matches!(self.modulation, ModulationMode::Noise_20220222) {
+            self.levels.iter_mut().for_each(|level| level.snr = snr_db);
+        }
+        self
+    }
+
+    /// The row in the original `X` dataset of each sampled frame, in `join`
+    /// order.
+    pub fn source_rows(&self) -> Vec<usize> {
+        self.sample_indices
+            .iter()
+            .enumerate()
+            .flat_map(|(l, indices)| indices.iter().map(move |&i| l * self.frames_per_level + i))
+            .collect()
+    }
+
+    /// Checks that the file has a level for each SNR of its mode (26, or 1
+    /// for noise), each holding as many frames as were sampled from it, all
+    /// of the same length.
+    pub fn validate_sampled(&self) -> Result<(), ValidationError> {
+        let expected = level_snrs(self.modulation).len();
+        if self.levels.len() != expected || self.sample_indices.len() != expected {
+            return Err(ValidationError::LevelCount {
+                modulation: self.modulation,
+                expected,
+                actual: self.levels.len(),
+            });
+        }
+        let frame_len = self.levels.first().map_or(1024, |s| s.iq.shape()[1]);
         self.levels
             .iter()
-            .for_each(|s| s.validate_sampled(self.sample_indices.len()));
+            .zip(self.sample_indices.iter())
+            .try_for_each(|(s, indices)| s.validate_sampled(indices.len(), frame_len))
+            .map_err(|error| error.for_mode(self.modulation))
+    }
+
+    /// Like `validate_sampled`, but panics on a mismatch, and prints a
+    /// summary of each level otherwise.
+    pub fn expect_valid(&self) {
+        println!("Validating sampled arrays ...");
+        if let Err(error) = self.validate_sampled() {
+            panic!("{}", error);
+        }
         self.levels.iter().for_each(|s| s.print_summary());
     }
 
@@ -233,19 +1942,32 @@ impl RadioFile {
         let sub_iqs = self.levels.par_iter().map(|i| i.iq.view()).collect::<Vec<_>>();
         let iq = ndarray::concatenate(Axis(0), &sub_iqs).unwrap();
 
-        if matches!(self.modulation, ModulationMode::Noise_20220222) {
-            assert_eq!([self.sample_indices.len(), 1024, 2], iq.shape());
-        } else {
-            assert_eq!([26 * self.sample_indices.len(), 1024, 2], iq.shape());
-        }
+        let num_frames = self.sample_indices.iter().map(|i| i.len()).sum::<usize>();
+        assert_eq!([num_frames, 1024, 2], iq.shape());
         (iq, self.modulation)
     }
+
+    /// The SNR label of each sampled frame, in `join` order.
+    pub fn frame_snrs(&self) -> Vec<i32> {
+        self.levels
+            .iter()
+            .flat_map(|level| std::iter::repeat(level.snr).take(level.iq.shape()[0]))
+            .collect()
+    }
+
+    /// Splits the file into what `RadioData` keeps of it.
+    fn into_part(self) -> FilePart {
+        let rows = self.source_rows();
+        let snrs = self.frame_snrs();
+        let (samples, mode) = self.join();
+        (mode, samples, rows, snrs)
+    }
 }
 
 #[derive(Debug)]
 pub struct SingleSnR {
     iq: Array3<f64>, // starts with (4096, 1024, 2). Sampled to (num_samples, 1024, 2)
-    snr: i32,        // -50dB for noise, otherwise one of (-20..=30).step_by(2)
+    snr: i32,        // NOISE_SNR_DB for noise, otherwise one of (-20..=30).step_by(2)
 }
 
 impl SingleSnR {
@@ -253,23 +1975,421 @@ impl SingleSnR {
         println!("snr: {}dB, shape: {:?}", self.snr, self.iq.shape());
     }
 
+    /// Up to `points` evenly spaced `(I, Q)` pairs from frame `frame_idx`,
+    /// including its first and last time steps, for logging a frame without
+    /// printing all of it.
+    ///
+    /// Returns `None` if there is no such frame.
+    pub fn preview(&self, frame_idx: usize, points: usize) -> Option<Vec<(f64, f64)>> {
+        if frame_idx >= self.iq.shape()[0] {
+            return None;
+        }
+        let frame = self.iq.index_axis(Axis(0), frame_idx);
+        let len = frame.nrows();
+        let points = points.min(len);
+        let preview = (0..points)
+            .map(|k| {
+                let t = if points == 1 {
+                    0
+                } else {
+                    k * (len - 1) / (points - 1)
+                };
+                (frame[[t, 0]], frame[[t, 1]])
+            })
+            .collect();
+        Some(preview)
+    }
+
     pub fn validate_full(&self) {
         assert_eq!([4096, 1024, 2], self.iq.shape());
     }
 
-    pub fn validate_sampled(&self, num_samples: usize) {
-        assert_eq!([num_samples, 1024, 2], self.iq.shape());
+    /// Checks that this level holds `num_samples` frames of `frame_len`
+    /// time steps, e.g. 1024 unless the frames were truncated or decimated.
+    pub fn validate_sampled(
+        &self,
+        num_samples: usize,
+        frame_len: usize,
+    ) -> Result<(), ValidationError> {
+        let expected = [num_samples, frame_len, 2];
+        if self.iq.shape() == expected {
+            Ok(())
+        } else {
+            Err(ValidationError::Shape {
+                modulation: None,
+                snr: Some(self.snr),
+                expected: expected.to_vec(),
+                actual: self.iq.shape().to_vec(),
+            })
+        }
+    }
+
+    /// Like `validate_sampled`, but panics on a mismatch.
+    pub fn expect_valid(&self, num_samples: usize, frame_len: usize) {
+        if let Err(error) = self.validate_sampled(num_samples, frame_len) {
+            panic!("{}", error);
+        }
+    }
+
+    /// Writes frames as CSV rows of `sample_index,time_index,i,q`.
+    ///
+    /// Every frame becomes 1024 rows (roughly 30 KB of text per frame), so
+    /// `max_frames` can cap how many frames are written.
+    pub fn write_csv(
+        &self,
+        path: impl AsRef<Path>,
+        max_frames: Option<usize>,
+    ) -> Result<(), String> {
+        let path = path.as_ref();
+        let write_err =
+            |reason: std::io::Error| format!("Could not write {:?} because {}", path, reason);
+
+        let file = std::fs::File::create(path).map_err(write_err)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "sample_index,time_index,i,q").map_err(write_err)?;
+
+        let num_frames = max_frames.map_or(self.iq.shape()[0], |m| m.min(self.iq.shape()[0]));
+        for (s, frame) in self.iq.outer_iter().take(num_frames).enumerate() {
+            for (t, iq) in frame.outer_iter().enumerate() {
+                writeln!(writer, "{},{},{},{}", s, t, iq[0], iq[1]).map_err(write_err)?;
+            }
+        }
+        writer.flush().map_err(write_err)
+    }
+
+    /// Keeps only the first `len` time steps of each frame.
+    pub fn truncate(&mut self, len: usize) -> Result<(), String> {
+        self.iq = truncate_frames(self.iq.view(), len)?;
+        Ok(())
+    }
+
+    /// Scales the I and Q channels separately to `[0, 1]` across all frames
+    /// and time steps, mapping constant channels to 0.5.
+    ///
+    /// Returns the per-channel minima and maxima so that the scaling can be
+    /// inverted or applied to other data.
+    pub fn minmax_normalize(&mut self) -> (Array1<f64>, Array1<f64>) {
+        let (mins, maxs) = channel_ranges(std::iter::once(self.iq.view()));
+        scale_channels(&mut self.iq, &mins, &maxs);
+        (mins, maxs)
+    }
+
+    /// Subtracts from each frame the mean of its I channel and of its Q
+    /// channel over time, removing any receiver DC offset.
+    pub fn remove_dc(&mut self) {
+        if let Some(means) = self.iq.mean_axis(Axis(1)) {
+            self.iq -= &means.insert_axis(Axis(1));
+        }
+    }
+
+    /// Like `remove_dc`, but returns the corrected frames and leaves these as
+    /// they are.
+    pub fn without_dc(&self) -> Array3<f64> {
+        match self.iq.mean_axis(Axis(1)) {
+            Some(means) => &self.iq - &means.insert_axis(Axis(1)),
+            None => self.iq.clone(),
+        }
+    }
+
+    /// Quantizes the frames to `i8` with one affine mapping over the whole
+    /// level, so that its minimum maps to -128 and its maximum to 127.
+    ///
+    /// Returns the quantized frames with the `scale` and `zero_point` that
+    /// `dequantize` needs to invert the mapping, which rounds each value to
+    /// within `scale / 2` of the original.
+    pub fn quantize_i8(&self) -> (Array3<i8>, f64, f64) {
+        let min = self.iq.fold(f64::INFINITY, |m, &v| m.min(v));
+        let max = self.iq.fold(f64::NEG_INFINITY, |m, &v| m.max(v));
+        let range = max - min;
+        let scale = if range > 0. { range / 255. } else { 1. };
+        let zero_point = -128. - min / scale;
+        let quantized = self
+            .iq
+            .mapv(|v| (v / scale + zero_point).round().clamp(-128., 127.) as i8);
+        (quantized, scale, zero_point)
+    }
+
+    /// Keeps every `factor`-th time step of each frame, giving frames of
+    /// length `1024 / factor`.
+    ///
+    /// With `anti_alias`, each kept sample is instead the mean of the
+    /// `factor` samples it replaces, a simple moving-average low-pass.
+    pub fn decimate(&self, factor: usize, anti_alias: bool) -> Result<Array3<f64>, String> {
+        let frame_len = self.iq.shape()[1];
+        if factor == 0 || frame_len % factor != 0 {
+            return Err(format!(
+                "Decimation factor {} does not evenly divide the frame length {}",
+                factor, frame_len
+            ));
+        }
+
+        if anti_alias {
+            let (n, _, c) = self.iq.dim();
+            let blocks = self
+                .iq
+                .view()
+                .into_shape((n, frame_len / factor, factor, c))
+                .map_err(|reason| format!("Could not decimate because {}", reason))?;
+            Ok(blocks.mean_axis(Axis(2)).unwrap())
+        } else {
+            Ok(self.iq.slice(s![.., ..;factor, ..]).to_owned())
+        }
     }
 
     pub fn subsample(self, indices: &[usize]) -> Self {
-        let iq_samples = indices
-            .par_iter()
-            .map(|&i| self.iq.index_axis(Axis(0), i))
-            .collect::<Vec<_>>();
-        let iq_samples = ndarray::stack(Axis(0), &iq_samples).unwrap();
         Self {
-            iq: iq_samples,
+            iq: self.iq.select(Axis(0), indices),
             snr: self.snr,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory for the fixture files of the test `name`.
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("radio_ml_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes a small file for `mode` in the original layout, with
+    /// `frames_per_level` frames in each SNR level, and returns its frames.
+    /// Every value identifies its row, time step and channel.
+    fn write_fixture(dir: &Path, mode: ModulationMode, frames_per_level: usize) -> Array3<f64> {
+        let snrs = if matches!(mode, ModulationMode::Noise_20220222) {
+            vec![NOISE_SNR_DB]
+        } else {
+            (-20..=30).step_by(2).collect()
+        };
+        let num_frames = snrs.len() * frames_per_level;
+        let x = Array3::from_shape_fn((num_frames, 1024, 2), |(f, t, c)| {
+            f as f64 + t as f64 / 1024. + c as f64 / 4096.
+        });
+        let mut y = Array2::<f64>::zeros((num_frames, ModulationMode::variants().len()));
+        y.column_mut(mode.index()).fill(1.);
+        let z = Array2::from_shape_fn((num_frames, 1), |(f, _)| {
+            f64::from(snrs[f / frames_per_level])
+        });
+
+        let file = hdf5::File::create(dir.join(mode.data_name())).unwrap();
+        file.new_dataset_builder()
+            .with_data(&x)
+            .create("X")
+            .unwrap();
+        file.new_dataset_builder()
+            .with_data(&y)
+            .create("Y")
+            .unwrap();
+        file.new_dataset_builder()
+            .with_data(&z)
+            .create("Z")
+            .unwrap();
+        x
+    }
+
+    #[test]
+    fn write_hdf5_round_trips_through_the_reader() {
+        let (source, copy) = (fixture_dir("write_source"), fixture_dir("write_copy"));
+        let mode = ModulationMode::BPSK;
+        write_fixture(&source, mode, 4);
+
+        let read = |dir: &Path| {
+            RadioReader::new(dir)
+                .modes(&[mode])
+                .num_samples(3)
+                .build()
+                .unwrap()
+        };
+        let data = read(&source);
+        data.write_hdf5(copy.join(mode.data_name()), "X").unwrap();
+        let reread = read(&copy);
+
+        assert_eq!(data.samples_for(mode), reread.samples_for(mode));
+        assert_eq!(data.labels(), reread.labels());
+        assert_eq!(data.snrs(), reread.snrs());
+    }
+
+    #[test]
+    fn write_hdf5_joins_every_mode_into_one_file() {
+        let dir = fixture_dir("write_joined");
+        let modes = [ModulationMode::BPSK, ModulationMode::Noise_20220222];
+        for &mode in &modes {
+            write_fixture(&dir, mode, 4);
+        }
+        let data = RadioReader::new(&dir)
+            .modes(&modes)
+            .num_samples(3)
+            .build()
+            .unwrap();
+        let path = dir.join("joined.h5");
+        data.write_hdf5(&path, "frames").unwrap();
+
+        let file = hdf5::File::open(&path).unwrap();
+        let frames = file.dataset("frames").unwrap().read::<f64, Ix3>().unwrap();
+        let onehot = file.dataset("Y").unwrap().read::<f64, Ix2>().unwrap();
+        let z = file.dataset("Z").unwrap().read::<f64, Ix2>().unwrap();
+        let labels = onehot
+            .outer_iter()
+            .map(|row| row.iter().position(|&v| v == 1.).unwrap())
+            .collect::<Array1<_>>();
+        assert_eq!(labels, data.labels());
+        assert_eq!(z.column(0).mapv(|snr| snr as i32), data.snrs());
+        assert_eq!(frames, data.join());
+    }
+
+    #[test]
+    fn validate_sampled_accepts_noise_truncated_and_partial_reads() {
+        let dir = fixture_dir("validate");
+        let modes = [ModulationMode::QPSK, ModulationMode::Noise_20220222];
+        for &mode in &modes {
+            write_fixture(&dir, mode, 4);
+        }
+        let reader = RadioReader::new(&dir).modes(&modes).num_samples(2);
+
+        let mut data = reader.clone().build().unwrap();
+        assert_eq!(data.validate_sampled(), Ok(()));
+        data.truncate(512).unwrap();
+        assert_eq!(data.validate_sampled(), Ok(()));
+        let partial = reader.snr_range(0, 10).build().unwrap();
+        assert_eq!(partial.validate_sampled(), Ok(()));
+
+        for &mode in &modes {
+            let handle = hdf5::File::open(dir.join(mode.data_name())).unwrap();
+            let file = RadioFile::new(handle, mode, 3).unwrap();
+            assert_eq!(file.validate_sampled(), Ok(()));
+        }
+        let handle = hdf5::File::open(dir.join(ModulationMode::QPSK.data_name())).unwrap();
+        let counts = (0..26).map(|l| l % 5).collect::<Vec<_>>();
+        let file = RadioFile::new_per_snr(handle, ModulationMode::QPSK, &counts, 0).unwrap();
+        assert_eq!(file.validate_sampled(), Ok(()));
+    }
+
+    #[test]
+    fn verify_snr_layout_rejects_short_z() {
+        let dir = fixture_dir("short_z");
+        write_fixture(&dir, ModulationMode::QPSK, 2);
+        let path = dir.join(ModulationMode::QPSK.data_name());
+        let file =
+            RadioFile::new(hdf5::File::open(&path).unwrap(), ModulationMode::QPSK, 1).unwrap();
+        assert_eq!(
+            file.verify_snr_layout(&hdf5::File::open(&path).unwrap()),
+            Ok(())
+        );
+
+        let handle = hdf5::File::open_rw(&path).unwrap();
+        handle.unlink("Z").unwrap();
+        handle
+            .new_dataset_builder()
+            .with_data(&Array2::<f64>::zeros((3, 1)))
+            .create("Z")
+            .unwrap();
+        assert!(file.verify_snr_layout(&handle).is_err());
+    }
+
+    #[test]
+    fn validate_sampled_reports_the_mismatch() {
+        let level = SingleSnR {
+            iq: Array3::zeros((3, 1024, 2)),
+            snr: 4,
+        };
+        assert_eq!(level.validate_sampled(3, 1024), Ok(()));
+        assert_eq!(
+            level.validate_sampled(2, 1024),
+            Err(ValidationError::Shape {
+                modulation: None,
+                snr: Some(4),
+                expected: vec![2, 1024, 2],
+                actual: vec![3, 1024, 2],
+            })
+        );
+    }
+
+    #[test]
+    fn partial_reads_convert_complex_x() {
+        let (real, complex) = (fixture_dir("real_x"), fixture_dir("complex_x"));
+        let mode = ModulationMode::QPSK;
+        let x = write_fixture(&real, mode, 4);
+        write_fixture(&complex, mode, 4);
+        let handle = hdf5::File::open_rw(complex.join(mode.data_name())).unwrap();
+        handle.unlink("X").unwrap();
+        let signal = Array2::from_shape_fn((x.shape()[0], x.shape()[1]), |(f, t)| H5Complex {
+            r: x[[f, t, 0]],
+            i: x[[f, t, 1]],
+        });
+        handle
+            .new_dataset_builder()
+            .with_data(&signal)
+            .create("X")
+            .unwrap();
+        drop(handle);
+
+        let reads: [&dyn Fn(hdf5::File) -> Result<RadioFile, ReaderError>; 3] = [
+            &|h| RadioFile::new_head(h, mode, 10),
+            &|h| RadioFile::new_indices(h, mode, &[3, 50, 7]),
+            &|h| RadioFile::new_buffered(h, mode, 2, 3),
+        ];
+        for read in reads {
+            let open = |dir: &Path| {
+                let handle = hdf5::File::open(dir.join(mode.data_name())).unwrap();
+                read(handle).unwrap().join().0
+            };
+            assert_eq!(open(&complex), open(&real));
+        }
+    }
+
+    #[test]
+    fn quantize_i8_round_trips_within_half_a_step() {
+        let iq = Array3::from_shape_fn((3, 1024, 2), |(f, t, c)| {
+            (f as f64 + t as f64 / 7. + c as f64).sin() * 0.02
+        });
+        let level = SingleSnR { iq, snr: 4 };
+        let (quantized, scale, zero_point) = level.quantize_i8();
+        assert_eq!(quantized.iter().min(), Some(&i8::MIN));
+        assert_eq!(quantized.iter().max(), Some(&i8::MAX));
+
+        let restored = dequantize(quantized.view(), scale, zero_point);
+        let error = (&restored - &level.iq).fold(0., |m: f64, &e| m.max(e.abs()));
+        assert!(error <= scale / 2. + 1e-12, "{error} > {}", scale / 2.);
+    }
+
+    #[test]
+    fn quantize_i8_keeps_constant_levels() {
+        let level = SingleSnR {
+            iq: Array3::from_elem((2, 1024, 2), 0.25),
+            snr: 0,
+        };
+        let (quantized, scale, zero_point) = level.quantize_i8();
+        assert_eq!(dequantize(quantized.view(), scale, zero_point), level.iq);
+    }
+
+    #[test]
+    fn frame_census_counts_num_samples_per_level() {
+        let dir = fixture_dir("census");
+        let modes = [
+            ModulationMode::BPSK,
+            ModulationMode::QPSK,
+            ModulationMode::Noise_20220222,
+        ];
+        for &mode in &modes {
+            write_fixture(&dir, mode, 4);
+        }
+        let data = RadioReader::new(&dir)
+            .modes(&modes)
+            .num_samples(3)
+            .build()
+            .unwrap();
+
+        // Each level holds `num_samples` frames, and noise is a single level.
+        let census = data.frame_census();
+        assert_eq!(census.len(), 26 + 26 + 1);
+        assert!(census.values().all(|&count| count == 3));
+        assert!(census.contains_key(&(ModulationMode::BPSK, -20)));
+        assert!(census.contains_key(&(ModulationMode::QPSK, 30)));
+        assert!(census.contains_key(&(ModulationMode::Noise_20220222, NOISE_SNR_DB)));
+        assert_eq!(census.values().sum::<usize>(), data.num_frames());
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use ndarray::prelude::*;
+use rayon::prelude::*;
+use rustfft::{Fft, FftPlanner};
+
+use crate::iq;
+use crate::window::WindowKind;
+
+/// Magnitude of the FFT of the complex signal in a `(n, 2)` frame.
+///
+/// This plans a new FFT on every call; use `spectra` for many frames.
+pub fn frame_spectrum(frame: ArrayView2<f64>) -> Array1<f64> {
+    let fft = FftPlanner::new().plan_fft_forward(frame.nrows());
+    magnitude_spectrum(fft.as_ref(), frame)
+}
+
+/// Magnitude spectra of every frame in a `(n_frames, n, 2)` array, as an
+/// `(n_frames, n)` matrix. A single FFT plan is shared across all frames.
+pub fn spectra(frames: ArrayView3<f64>) -> Array2<f64> {
+    let fft: Arc<dyn Fft<f64>> = FftPlanner::new().plan_fft_forward(frames.shape()[1]);
+    let rows = (0..frames.shape()[0])
+        .into_par_iter()
+        .map(|i| magnitude_spectrum(fft.as_ref(), frames.index_axis(Axis(0), i)))
+        .collect::<Vec<_>>();
+    let rows = rows.iter().map(|r| r.view()).collect::<Vec<_>>();
+    ndarray::stack(Axis(0), &rows).unwrap()
+}
+
+fn magnitude_spectrum(fft: &dyn Fft<f64>, frame: ArrayView2<f64>) -> Array1<f64> {
+    let mut signal = iq::to_complex(frame);
+    fft.process(&mut signal);
+    signal.iter().map(|c| c.norm()).collect()
+}
+
+/// Power spectral density of a `(n, 2)` frame by Welch's method.
+///
+/// The complex signal is split into segments of `segment_len` samples that
+/// overlap by `overlap` samples. Each segment is Hann windowed and its
+/// periodogram computed, and the periodograms are averaged. Samples past the
+/// last whole segment are dropped.
+pub fn welch_psd(
+    frame: ArrayView2<f64>,
+    segment_len: usize,
+    overlap: usize,
+) -> Result<Array1<f64>, String> {
+    if segment_len == 0 || segment_len > frame.nrows() {
+        return Err(format!(
+            "segment_len must be in 1..={}, got {}",
+            frame.nrows(),
+            segment_len
+        ));
+    }
+    if overlap >= segment_len {
+        return Err(format!(
+            "overlap ({}) must be smaller than segment_len ({})",
+            overlap, segment_len
+        ));
+    }
+
+    let signal = iq::to_complex(frame);
+    let window = WindowKind::Hann.coefficients(segment_len);
+    let window_power = window.iter().map(|w| w * w).sum::<f64>();
+    let fft = FftPlanner::new().plan_fft_forward(segment_len);
+
+    let step = segment_len - overlap;
+    let num_segments = (signal.len() - segment_len) / step + 1;
+    let mut psd = Array1::zeros(segment_len);
+    for s in 0..num_segments {
+        let start = s * step;
+        let mut segment = signal[start..start + segment_len]
+            .iter()
+            .zip(window.iter())
+            .map(|(&c, &w)| c * w)
+            .collect::<Vec<_>>();
+        fft.process(&mut segment);
+        psd.iter_mut()
+            .zip(segment.iter())
+            .for_each(|(p, c)| *p += c.norm_sqr() / window_power);
+    }
+
+    Ok(psd / num_segments as f64)
+}
+
+/// Magnitude spectrogram of a `(n, 2)` frame by the short-time Fourier
+/// transform.
+///
+/// Segments of `window_len` samples start every `hop` samples and are Hann
+/// windowed before the FFT. Samples past the last whole segment are
+/// dropped. The result has shape `(n_segments, window_len)`.
+pub fn stft(frame: ArrayView2<f64>, window_len: usize, hop: usize) -> Result<Array2<f64>, String> {
+    if window_len == 0 || window_len > frame.nrows() {
+        return Err(format!(
+            "window_len must be in 1..={}, got {}",
+            frame.nrows(),
+            window_len
+        ));
+    }
+    if hop == 0 {
+        return Err("hop must be positive".to_string());
+    }
+
+    let signal = iq::to_complex(frame);
+    let window = WindowKind::Hann.coefficients(window_len);
+    let fft = FftPlanner::new().plan_fft_forward(window_len);
+
+    let num_segments = (signal.len() - window_len) / hop + 1;
+    let mut spectrogram = Array2::zeros((num_segments, window_len));
+    for (s, mut row) in spectrogram.outer_iter_mut().enumerate() {
+        let start = s * hop;
+        let mut segment = signal[start..start + window_len]
+            .iter()
+            .zip(window.iter())
+            .map(|(&c, &w)| c * w)
+            .collect::<Vec<_>>();
+        fft.process(&mut segment);
+        row.iter_mut()
+            .zip(segment.iter())
+            .for_each(|(m, c)| *m = c.norm());
+    }
+
+    Ok(spectrogram)
+}
@@ -0,0 +1,72 @@
+//! Tapering windows applied along the time axis of frames.
+
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl WindowKind {
+    /// The symmetric window of length `n`. Windows of fewer than two
+    /// samples have no taper, so they are all ones.
+    pub fn coefficients(&self, n: usize) -> Array1<f64> {
+        if n <= 1 {
+            return Array1::ones(n);
+        }
+        let step = 2. * PI / (n - 1) as f64;
+        Array1::from_shape_fn(n, |k| {
+            let t = step * k as f64;
+            match self {
+                Self::Rectangular => 1.,
+                Self::Hann => 0.5 - 0.5 * t.cos(),
+                Self::Hamming => 0.54 - 0.46 * t.cos(),
+                Self::Blackman => 0.42 - 0.5 * t.cos() + 0.08 * (2. * t).cos(),
+            }
+        })
+    }
+}
+
+/// Multiplies both the I and Q channels of a `(n, 2)` frame by the window,
+/// which is the same as windowing the complex signal.
+pub fn apply_window(frame: ArrayView2<f64>, kind: WindowKind) -> Array2<f64> {
+    let window = kind.coefficients(frame.nrows());
+    &frame * &window.insert_axis(Axis(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [WindowKind; 4] = [
+        WindowKind::Rectangular,
+        WindowKind::Hann,
+        WindowKind::Hamming,
+        WindowKind::Blackman,
+    ];
+
+    #[test]
+    fn short_windows_have_no_taper() {
+        for kind in KINDS {
+            assert_eq!(kind.coefficients(0), Array1::<f64>::zeros(0));
+            assert_eq!(kind.coefficients(1), array![1.]);
+        }
+    }
+
+    #[test]
+    fn applies_to_empty_and_one_sample_frames() {
+        for kind in KINDS {
+            assert_eq!(
+                apply_window(Array2::zeros((0, 2)).view(), kind).dim(),
+                (0, 2)
+            );
+            let frame = array![[0.5, -2.]];
+            assert_eq!(apply_window(frame.view(), kind), frame);
+        }
+    }
+}